@@ -1,6 +1,15 @@
-use advent2023_lib::{get_days, get_input, DayTrait, Part, PrimaryExample};
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use advent2023_lib::{
+    day01, day02, day03, day04, day07, day08, get_days, get_input, CalcResult, Context, DayTrait,
+    Params, Part, PrimaryExample, SolveError,
+};
+use color_eyre::eyre::bail;
 use color_eyre::Report;
 use colored::*;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
@@ -15,49 +24,426 @@ struct Cli {
 
     #[structopt(long)]
     example: bool,
+
+    /// Print the day 8 network as a Graphviz DOT graph
+    #[structopt(long)]
+    dot: bool,
+
+    /// The example to use with --example, numbered from 1 as in the web view
+    #[structopt(long, default_value = "1")]
+    example_num: usize,
+
+    /// Play day 7 with the Camel Cards rules in this file
+    #[structopt(long)]
+    rules: Option<std::path::PathBuf>,
+
+    /// Explain how every day 7 hand is ranked, with jokers unless --rules is given
+    #[structopt(long)]
+    explain: bool,
+
+    /// Solve the puzzle with this implementation and time each part
+    #[structopt(long)]
+    implementation: Option<String>,
+
+    /// Solve each part this many times with --implementation and report the mean time
+    #[structopt(long, default_value = "1")]
+    repeat: u32,
+
+    /// Calibrate day 1 with these spelled-out digits: digits, english, english_zero or dutch
+    #[structopt(long)]
+    vocabulary: Option<day01::Vocabulary>,
+
+    /// List the numbers touched by each kind of symbol in the day 3 schematic
+    #[structopt(long)]
+    symbols: bool,
+
+    /// Check which day 2 games are possible with a bag such as "12 red, 13 green, 14 blue"
+    #[structopt(long)]
+    bag: Option<String>,
+
+    /// Show how many copies of each day 4 scratchcard are won
+    #[structopt(long)]
+    pile: bool,
+
+    /// Change a parameter of the puzzle, such as red=20 for day 2
+    #[structopt(long = "param", parse(try_from_str = parse_assignment))]
+    params: Vec<(String, String)>,
+
+    /// Give up on each part that runs for longer than this many seconds
+    #[structopt(long, parse(try_from_str = parse_timeout))]
+    timeout: Option<Duration>,
+}
+
+fn parse_assignment(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(name, value)| (name.to_owned(), value.to_owned()))
+        .ok_or(format!("expected name=value, found {}", s))
+}
+
+fn parse_timeout(s: &str) -> Result<Duration, String> {
+    let seconds: f64 = s.parse().map_err(|e| format!("{}", e))?;
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|_e| format!("expected a number of seconds, found {}", s))
+}
+
+/// The context of one run of a part, with a progress bar that is only shown
+/// once the part reports progress.
+struct PartRun {
+    context: Context,
+    bar: Arc<OnceLock<ProgressBar>>,
+}
+
+impl PartRun {
+    /// A run that times out if a timeout is given.
+    fn new(timeout: Option<Duration>, bars: &MultiProgress, label: String) -> Self {
+        let context = match timeout {
+            Some(timeout) => Context::with_timeout(timeout),
+            None => Context::default(),
+        };
+        let bar = Arc::new(OnceLock::<ProgressBar>::new());
+        let context = {
+            let (bars, bar) = (bars.clone(), Arc::clone(&bar));
+            context.with_progress(move |progress| {
+                let bar = bar.get_or_init(|| {
+                    let style = ProgressStyle::with_template("{msg} {wide_bar} {pos}/{len}")
+                        .expect("the template is valid");
+                    bars.add(
+                        ProgressBar::new(0)
+                            .with_style(style)
+                            .with_message(label.clone()),
+                    )
+                });
+                if let Some(total) = progress.total {
+                    bar.set_length(total);
+                }
+                bar.set_position(progress.done);
+            })
+        };
+        Self { context, bar }
+    }
+
+    fn finish(self) {
+        if let Some(bar) = self.bar.get() {
+            bar.finish_and_clear();
+        }
+    }
 }
 
-fn print_day<O: std::fmt::Display>(
+/// The parameters of a day, with the assignments to the parameters it has.
+fn day_params(day: &dyn DayTrait, assignments: &[(String, String)]) -> Result<Params, Report> {
+    let mut params = Params::new(day.get_params());
+    for (name, value) in assignments {
+        if day.get_params().iter().any(|spec| spec.name == name) {
+            if let Err(e) = params.set(name, value) {
+                bail!("invalid value {} for parameter {}: {}", value, name, e);
+            }
+        }
+    }
+    Ok(params)
+}
+
+fn print_day(
     day_num: usize,
     display: (&'static str, &'static str),
-    result: (O, O),
+    results: [CalcResult<String>; 2],
 ) {
     println!("Day {}", day_num);
+    for (part_num, (display, result)) in (1..).zip([display.0, display.1].into_iter().zip(results))
+    {
+        match result {
+            Ok(answer) => println!(
+                "Part {}: {}",
+                part_num,
+                display.replace("{answer}", &answer)
+            ),
+            Err(e) if e.is_cancelled() => println!("Part {}: {}", part_num, "timed out".yellow()),
+            Err(e) => println!("Part {}: {}", part_num, format!("error, {}", e).red()),
+        }
+    }
+    println!();
+}
+
+fn print_hand_reports(reports: &[day07::HandReport]) {
+    let cards = |cards: &[day07::Card; 5]| -> String {
+        cards.iter().map(|card| card.to_string()).collect()
+    };
     println!(
-        "Part 1: {}",
-        display.0.replace("{answer}", &result.0.to_string())
-    );
-    println!(
-        "Part 2: {}",
-        display.1.replace("{answer}", &result.1.to_string())
+        "{:<6} {:<16} {:<5} {:<9} {:<16} {:>5} {:>5} {:>9}",
+        "Hand", "Type", "Wild", "Effective", "Effective type", "Bid", "Rank", "Winnings"
     );
+    for report in reports {
+        println!(
+            "{:<6} {:<16} {:<5} {:<9} {:<16} {:>5} {:>5} {:>9}",
+            cards(&report.cards),
+            report.hand_type.to_string(),
+            report
+                .substitute
+                .map_or(String::from("-"), |card| card.to_string()),
+            cards(&report.effective_cards),
+            report.effective_type.to_string(),
+            report.bid,
+            report.rank,
+            report.winnings,
+        );
+    }
     println!();
 }
 
 fn main() -> Result<(), Report> {
     setup()?;
 
+    let args = Cli::from_args();
+    let days = get_days();
+
+    if args.dot {
+        let input = if args.example {
+            let examples = days[&8].get_all_examples();
+            let Some((_part, example)) = args
+                .example_num
+                .checked_sub(1)
+                .and_then(|index| examples.get(index))
+            else {
+                bail!(
+                    "day 8 has {} examples, so there is no example {}",
+                    examples.len(),
+                    args.example_num
+                );
+            };
+            example.input.to_owned()
+        } else {
+            get_input(8)
+        };
+        println!("{}", day08::parse(&input)?.to_dot());
+        return Ok(());
+    }
+
     println!("{}", "Advent Of Code 2022".bold().blue());
     println!();
 
-    let args = Cli::from_args();
-    let days = get_days();
+    if let Some(vocabulary) = args.vocabulary {
+        let input = if args.example {
+            day01::DAY.examples.part2[0].input.to_owned()
+        } else {
+            get_input(1)
+        };
+        let document = day01::parse(&input)?;
+        println!(
+            "The sum of the calibration values with {} digits is {}.",
+            vocabulary,
+            day01::calibration_sum(&document, vocabulary)?
+        );
+        return Ok(());
+    }
 
-    let get_result_pair = move |day_num: usize, day: &dyn DayTrait| -> (String, String) {
-        if args.example {
-            match day.get_examples() {
-                PrimaryExample::Same(example) => {
-                    day.both(example).expect("example should be valid")
-                },
-                PrimaryExample::Different([first, second]) => (
-                    day.calc(Part::First, first).unwrap(),
-                    day.calc(Part::Second, second).unwrap(),
+    if let Some(bag) = &args.bag {
+        let bag: day02::CubeSet = bag.parse()?;
+        let input = if args.example {
+            day02::DAY.examples.common[0].input.to_owned()
+        } else {
+            get_input(2)
+        };
+        let records = day02::parse(&input)?;
+        for game in day02::feasibility(&records, &bag) {
+            match game.violation {
+                None => println!(
+                    "Game {}: possible, needs at least {}",
+                    game.id, game.minimal_bag
+                ),
+                Some(violation) => println!(
+                    "Game {}: impossible, draw {} ({}) has {} {} but the bag holds {}",
+                    game.id,
+                    violation.draw + 1,
+                    violation.hand,
+                    violation.drawn,
+                    violation.colour,
+                    violation.available
                 ),
             }
+        }
+        return Ok(());
+    }
+
+    if args.pile {
+        let input = if args.example {
+            day04::DAY.examples.common[0].input.to_owned()
+        } else {
+            get_input(4)
+        };
+        let pile = day04::parse(&input)?;
+        println!(
+            "{:>5} {:>8} {:>10} {:>10}",
+            "Card", "Matching", "Copies", "Pile"
+        );
+        let mut total = 0;
+        for card in day04::copies(&pile) {
+            total += card.copies;
+            println!(
+                "{:>5} {:>8} {:>10} {:>10}",
+                card.id, card.matching, card.copies, total
+            );
+        }
+        return Ok(());
+    }
+
+    if args.symbols {
+        let input = if args.example {
+            day03::DAY.examples.common[0].input.to_owned()
+        } else {
+            get_input(3)
+        };
+        let schematic = day03::parse(&input)?;
+        println!(
+            "{:<6} {:>5} {:>7} {:>9}",
+            "Symbol", "Count", "Numbers", "Sum"
+        );
+        for statistics in day03::symbol_statistics(&schematic) {
+            println!(
+                "{:<6} {:>5} {:>7} {:>9}",
+                statistics.symbol, statistics.count, statistics.numbers, statistics.sum
+            );
+        }
+        let (_part_numbers, unattached) = schematic.part_numbers();
+        println!(
+            "{:<6} {:>5} {:>7} {:>9}",
+            "none",
+            "",
+            unattached.len(),
+            unattached.iter().map(|number| number.value).sum::<usize>()
+        );
+        return Ok(());
+    }
+
+    if args.rules.is_some() || args.explain {
+        let rules: day07::Rules = match &args.rules {
+            Some(path) => std::fs::read_to_string(path)?.parse()?,
+            None => day07::Rules::jokers(),
+        };
+        let input = if args.example {
+            day07::DAY.examples.common[0].input.to_owned()
+        } else {
+            get_input(7)
+        };
+        let cards = day07::parse(&input)?;
+        if args.explain {
+            print_hand_reports(&day07::explain(&cards, &rules)?);
+        }
+        println!(
+            "The total winnings are {}.",
+            day07::total_winnings(&cards, &rules)?
+        );
+        return Ok(());
+    }
+
+    let bars = MultiProgress::new();
+    let puzzle = args
+        .puzzle
+        .unwrap_or_else(|| *days.keys().next_back().unwrap());
+    for (name, _value) in &args.params {
+        let mut selected = days
+            .iter()
+            .filter(|(day_num, _day)| args.all || args.parallel || **day_num == puzzle);
+        if !selected.any(|(_day_num, day)| day.get_params().iter().any(|spec| spec.name == name)) {
+            bail!("no selected puzzle has a parameter called {}", name);
+        }
+    }
+
+    if let Some(implementation) = &args.implementation {
+        let day_num = puzzle;
+        let day = &days[&day_num];
+        let params = day_params(day.as_ref(), &args.params)?;
+        let inputs = if args.example {
+            match day.get_examples() {
+                PrimaryExample::Same(example) => [example.to_owned(), example.to_owned()],
+                PrimaryExample::Different([first, second]) => [first.to_owned(), second.to_owned()],
+            }
         } else {
             let input = get_input(day_num);
-            day.both(&input).expect("invalid input")
+            [input.clone(), input]
+        };
+        let repeat = args.repeat.max(1);
+        println!("Day {}", day_num);
+        for ((part, part_num), input) in [(Part::First, 1), (Part::Second, 2)]
+            .into_iter()
+            .zip(inputs)
+        {
+            if !day.implementations(part).contains(&implementation.as_str()) {
+                println!(
+                    "Part {} has no {} implementation, choose from: {}",
+                    part_num,
+                    implementation,
+                    day.implementations(part).join(", ")
+                );
+                continue;
+            }
+            let start = std::time::Instant::now();
+            let session = day.session(&input)?;
+            println!("Part {} parsed in {:.2?}", part_num, start.elapsed());
+            let start = std::time::Instant::now();
+            let mut result = None;
+            for _ in 0..repeat {
+                let run = PartRun::new(
+                    args.timeout,
+                    &bars,
+                    format!("Day {} part {}", day_num, part_num),
+                );
+                result = session.run_with(part, implementation, &params, &run.context);
+                run.finish();
+            }
+            let elapsed = start.elapsed() / repeat;
+            match result.expect("the implementation was listed") {
+                Ok(answer) => println!(
+                    "Part {} ({}) took {:.2?}: {}",
+                    part_num, implementation, elapsed, answer
+                ),
+                Err(SolveError::Cancelled) => println!(
+                    "Part {} ({}) {}",
+                    part_num,
+                    implementation,
+                    "timed out".yellow()
+                ),
+                Err(e) => println!(
+                    "Part {} ({}) failed: {}",
+                    part_num,
+                    implementation,
+                    format!("{}", e).red()
+                ),
+            }
         }
+        return Ok(());
+    }
+
+    // Check the parameters of every day to solve before solving any of them.
+    let all_params = days
+        .iter()
+        .filter(|(day_num, _day)| args.all || args.parallel || **day_num == puzzle)
+        .map(|(&day_num, day)| Ok((day_num, day_params(day.as_ref(), &args.params)?)))
+        .collect::<Result<BTreeMap<usize, Params>, Report>>()?;
+    let get_part_results = move |day_num: usize, day: &dyn DayTrait| -> [CalcResult<String>; 2] {
+        let params = &all_params[&day_num];
+        let [first, second] = if args.example {
+            match day.get_examples() {
+                PrimaryExample::Same(example) => [example.to_owned(), example.to_owned()],
+                PrimaryExample::Different([first, second]) => [first.to_owned(), second.to_owned()],
+            }
+        } else {
+            let input = get_input(day_num);
+            [input.clone(), input]
+        };
+        let solve = |part, part_num, input: &str| {
+            let run = PartRun::new(
+                args.timeout,
+                &bars,
+                format!("Day {} part {}", day_num, part_num),
+            );
+            let answer = day.calc(part, input, params, &run.context);
+            run.finish();
+            answer
+        };
+        // Each part is solved and reported even if the other fails.
+        [
+            solve(Part::First, 1, &first),
+            solve(Part::Second, 2, &second),
+        ]
     };
 
     if args.all {
@@ -65,39 +451,38 @@ fn main() -> Result<(), Report> {
             print_day(
                 day_num,
                 day.get_display(),
-                get_result_pair(day_num, day.as_ref()),
+                get_part_results(day_num, day.as_ref()),
             );
         }
     } else if args.parallel {
-        let threads = get_days().into_iter().map(|(day_num, day)| {
-            println!("Spawn day {}", day_num);
-            std::thread::spawn(move || {
-                (
-                    day_num,
-                    day.get_display(),
-                    get_result_pair(day_num, day.as_ref()),
-                )
+        let threads: Vec<_> = days
+            .iter()
+            .map(|(&day_num, day)| {
+                println!("Spawn day {}", day_num);
+                let day = Arc::clone(day);
+                let get_part_results = get_part_results.clone();
+                std::thread::spawn(move || {
+                    (
+                        day_num,
+                        day.get_display(),
+                        get_part_results(day_num, day.as_ref()),
+                    )
+                })
             })
-        });
+            .collect();
         std::thread::yield_now();
-        std::thread::sleep(std::time::Duration::from_millis(50));
+        std::thread::sleep(Duration::from_millis(50));
         println!();
         for thread in threads {
-            let (day_num, display, (part1, part2)) = thread.join().unwrap();
-            print_day(day_num, display, (part1, part2));
+            let (day_num, display, result) = thread.join().unwrap();
+            print_day(day_num, display, result);
         }
     } else if !(args.all || args.parallel) {
-        let (day_num, day): (usize, _) = match args.puzzle {
-            None => {
-                let (last_day_num, last_day) = days.iter().next_back().unwrap();
-                (*last_day_num, last_day)
-            },
-            Some(day_num) => (day_num, days.get(&day_num).unwrap()),
-        };
+        let (day_num, day): (usize, _) = (puzzle, days.get(&puzzle).unwrap());
         print_day(
             day_num,
             day.get_display(),
-            get_result_pair(day_num, day.as_ref()),
+            get_part_results(day_num, day.as_ref()),
         );
     }
 
@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use crate::{Day, DayCalc, Examples, ParseError, ParseResult, PartOutput};
+use crate::{Context, Day, DayCalc, Example, Examples, Params, ParseError, ParseResult, PartOutput, PartResult};
 
 #[derive(Debug)]
 pub struct Something {}
@@ -17,12 +17,12 @@ pub fn parse(input: &str) -> ParseResult<Something> {
     Ok(Something {})
 }
 
-pub fn part1(something: &Something) -> PartOutput<usize> {
-    PartOutput { answer: 0 }
+pub fn part1(something: &Something, _params: &Params, _context: &Context) -> PartResult<usize> {
+    Ok(PartOutput { answer: 0 })
 }
 
-pub fn part2(_something: &Something) -> PartOutput<usize> {
-    PartOutput { answer: 0 }
+pub fn part2(_something: &Something, _params: &Params, _context: &Context) -> PartResult<usize> {
+    Ok(PartOutput { answer: 0 })
 }
 
 pub const DAY: Day<Something, usize, 1, 0, 0> = Day {
@@ -31,12 +31,14 @@ pub const DAY: Day<Something, usize, 1, 0, 0> = Day {
         "Foobar foobar foobar {answer}.",
         "Foobar foobar foobar {answer}.",
     ),
+    params: &[],
     calc: DayCalc {
         parse,
         part1,
         part2,
+        alternatives: &[],
     },
-    examples: Examples::single(include_str!("../../examples/day00.in.txt")),
+    examples: Examples::single(Example::new(include_str!("../../examples/day00.in.txt"))),
 };
 
 #[cfg(test)]
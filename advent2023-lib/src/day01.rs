@@ -1,143 +1,152 @@
-use strum::IntoEnumIterator;
-use strum_macros::{Display, EnumIter, EnumString, FromRepr};
+use std::fmt::Display;
 
-use crate::parser::read_vec2;
-use crate::{Day, DayCalc, Examples, ParseResult, PartOutput};
+use strum_macros::{Display, EnumIter, EnumString};
 
-pub struct Chars(Vec<Vec<char>>);
+use crate::scanner::Scanner;
+use crate::{
+    Context, Day, DayCalc, Example, Examples, Params, ParseResult, Part, PartOutput, PartResult,
+    SolveError,
+};
 
-pub fn parse(input: &str) -> ParseResult<Chars> {
-    Ok(Chars(read_vec2::<char>(input)?))
-}
+pub struct CalibrationDocument(Vec<String>);
 
-pub fn part1(chars: &Chars) -> PartOutput<usize> {
-    PartOutput {
-        answer: chars
-            .0
-            .iter()
-            .map(|line| {
-                let first = line
-                    .iter()
-                    .find(|c| c.is_numeric())
-                    .unwrap()
-                    .to_digit(10)
-                    .unwrap();
-                let last = line
-                    .iter()
-                    .rev()
-                    .find(|c| c.is_numeric())
-                    .unwrap()
-                    .to_digit(10)
-                    .unwrap();
-                usize::try_from(10 * first + last).unwrap()
-            })
-            .sum::<usize>(),
-    }
+pub fn parse(input: &str) -> ParseResult<CalibrationDocument> {
+    Ok(CalibrationDocument(
+        input.lines().map(str::to_owned).collect(),
+    ))
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Display, EnumString, EnumIter, FromRepr)]
+/// The set of words, besides the digits themselves, that spell a digit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display, EnumString, EnumIter)]
 #[strum(serialize_all = "snake_case")]
-enum Digit {
-    One = 1,
-    Two = 2,
-    Three = 3,
-    Four = 4,
-    Five = 5,
-    Six = 6,
-    Seven = 7,
-    Eight = 8,
-    Nine = 9,
+pub enum Vocabulary {
+    Digits,
+    English,
+    EnglishZero,
+    Dutch,
 }
 
-impl Digit {
-    fn val(&self) -> usize {
-        *self as usize
+impl Vocabulary {
+    fn words(self) -> &'static [&'static str] {
+        match self {
+            Self::Digits => &[],
+            Self::English => &[
+                "", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+            ],
+            Self::EnglishZero => &[
+                "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+            ],
+            Self::Dutch => &[
+                "", "een", "twee", "drie", "vier", "vijf", "zes", "zeven", "acht", "negen",
+            ],
+        }
     }
 
-    fn find_first(s: &[char]) -> Option<Self> {
-        for index in 0..s.len() {
-            if s[index].is_numeric() {
-                return Self::from_repr(s[index].to_digit(10).unwrap().try_into().unwrap());
-            }
-            for color in Self::iter() {
-                let color_str = color.to_string();
-                let end_index = index + color_str.len();
-                if let Some(potential) =
-                    s.get(index..end_index).map(|s| String::from_iter(s.iter()))
-                {
-                    log::trace!("comparing {} to {}", potential, color_str);
-                    if potential == color_str {
-                        log::trace!("matched {}", color_str);
-                        return Some(color);
-                    }
-                }
-            }
-        }
-        log::error!("no match for {}", String::from_iter(s.iter()));
-        None
+    pub fn scanner(self) -> Scanner<usize> {
+        let digits = (0..=9).map(|digit| (digit.to_string(), digit));
+        let words = (0..)
+            .zip(self.words())
+            .filter(|(_digit, word)| !word.is_empty())
+            .map(|(digit, word)| (word.to_string(), digit));
+        Scanner::new(digits.chain(words))
     }
+}
 
-    fn find_last(s: &[char]) -> Option<Self> {
-        for index in (0..s.len()).rev() {
-            if s[index].is_numeric() {
-                return Self::from_repr(s[index].to_digit(10).unwrap().try_into().unwrap());
-            }
-            for color in Self::iter() {
-                let color_str = color.to_string();
-                if let Some(start_index) =
-                    index.checked_add(1).unwrap().checked_sub(color_str.len())
-                {
-                    if let Some(potential) = s
-                        .get(start_index..=index)
-                        .map(|s| String::from_iter(s.iter()))
-                    {
-                        log::trace!("comparing {} to {}", potential, color_str);
-                        if potential == color_str {
-                            log::trace!("matched {}", color_str);
-                            return Some(color);
-                        }
-                    }
-                }
-            }
-        }
-        log::error!("no match for {}", String::from_iter(s.iter()));
-        None
+#[derive(Debug)]
+pub struct NoDigitError(String);
+
+impl Display for NoDigitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no digit in line {}", self.0)
     }
 }
 
-pub fn part2(chars: &Chars) -> PartOutput<usize> {
-    PartOutput {
-        answer: chars
-            .0
-            .iter()
-            .map(|line| {
-                let first = Digit::find_first(line).unwrap();
-                let last = Digit::find_last(line).unwrap();
-                log::debug!(
-                    "on line {:?} the first digit is {} and the last is {}",
-                    String::from_iter(line.iter()),
-                    first,
-                    last,
-                );
-                10 * first.val() + last.val()
-            })
-            .sum::<usize>(),
+impl std::error::Error for NoDigitError {}
+
+impl From<NoDigitError> for SolveError {
+    fn from(value: NoDigitError) -> Self {
+        Self::Str(value.to_string())
     }
 }
 
-pub const DAY: Day<Chars, usize, 0, 1, 1> = Day {
+pub fn calibration_sum(
+    document: &CalibrationDocument,
+    vocabulary: Vocabulary,
+) -> Result<usize, NoDigitError> {
+    let scanner = vocabulary.scanner();
+    document
+        .0
+        .iter()
+        .map(|line| {
+            let (first, last) = scanner
+                .first_last(line)
+                .ok_or_else(|| NoDigitError(line.clone()))?;
+            log::debug!(
+                "on line {:?} the first digit is {} and the last is {}",
+                line,
+                first,
+                last,
+            );
+            Ok(10 * first + last)
+        })
+        .sum()
+}
+
+pub fn part1(
+    document: &CalibrationDocument,
+    _params: &Params,
+    _context: &Context,
+) -> PartResult<usize> {
+    Ok(PartOutput {
+        answer: calibration_sum(document, Vocabulary::Digits)?,
+    })
+}
+
+pub fn part2(
+    document: &CalibrationDocument,
+    _params: &Params,
+    _context: &Context,
+) -> PartResult<usize> {
+    Ok(PartOutput {
+        answer: calibration_sum(document, Vocabulary::English)?,
+    })
+}
+
+pub const DAY: Day<CalibrationDocument, usize, 0, 1, 1> = Day {
     title: "Trebuchet?!",
     display: (
         "The sum of all the numeric calibration values is {answer}.",
         "The sum of all the alphanumeric calibration values is {answer}.",
     ),
+    params: &[],
     calc: DayCalc {
         parse,
         part1,
         part2,
+        alternatives: &[],
     },
     examples: Examples::pair(
-        include_str!("../../examples/day01-1.txt"),
-        include_str!("../../examples/day01-2.txt"),
+        Example::new(include_str!("../../examples/day01-1.txt")).answer(Part::First, "142"),
+        Example::new(include_str!("../../examples/day01-2.txt")).answer(Part::Second, "281"),
     ),
 };
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_vocabulary() {
+        let sum = |input, vocabulary| calibration_sum(&parse(input).unwrap(), vocabulary);
+        assert_eq!(sum("xtwone3four", Vocabulary::English).unwrap(), 24);
+        assert_eq!(sum("zeroxone", Vocabulary::English).unwrap(), 11);
+        assert_eq!(sum("zeroxone", Vocabulary::EnglishZero).unwrap(), 1);
+        assert_eq!(
+            sum("tweeacht3\nzevenegen", Vocabulary::Dutch).unwrap(),
+            23 + 79
+        );
+        assert!(sum("one", Vocabulary::Digits).is_err());
+    }
+}
@@ -1,44 +1,82 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
 use std::str::FromStr;
 
 use once_cell::sync::Lazy;
 use regex::Regex;
 
-use crate::parser::read_vec1;
-use crate::{Day, DayCalc, Examples, ParseError, ParseResult, PartOutput};
+use crate::parser::read_lines;
+use crate::{
+    Context, Day, DayCalc, Example, Examples, Param, ParamKind, Params, ParseError, ParseResult,
+    PartOutput, PartResult,
+};
 
-#[derive(Debug)]
-pub struct Hand {
-    red: usize,
-    green: usize,
-    blue: usize,
-}
+/// A number of cubes of each colour, written as `3 blue, 4 red`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CubeSet(BTreeMap<String, usize>);
 
-impl FromStr for Hand {
+impl FromStr for CubeSet {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut red = 0;
-        let mut green = 0;
-        let mut blue = 0;
+        let mut cubes = BTreeMap::new();
         for num_colour in s.split(", ") {
             let (num, col) = num_colour
                 .split_once(' ')
                 .ok_or(ParseError::Str(format!("unexpected {}", num_colour)))?;
-            match col {
-                "red" => red = num.parse()?,
-                "green" => green = num.parse()?,
-                "blue" => blue = num.parse()?,
-                _ => return Err(ParseError::Str(format!("unexpected {}", col))),
+            if cubes.insert(col.to_owned(), num.parse()?).is_some() {
+                return Err(ParseError::Str(format!("{} appears twice in {}", col, s)));
             }
         }
-        Ok(Hand { red, green, blue })
+        Ok(Self(cubes))
+    }
+}
+
+impl Display for CubeSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.0
+                .iter()
+                .map(|(colour, count)| format!("{} {}", count, colour))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl CubeSet {
+    pub fn get(&self, colour: &str) -> usize {
+        self.0.get(colour).copied().unwrap_or(0)
+    }
+
+    /// The product of the number of cubes in the set of each colour in the
+    /// bag, so that a colour the set lacks makes it 0.
+    pub fn power(&self, bag: &CubeSet) -> usize {
+        bag.0.keys().map(|colour| self.get(colour)).product()
     }
 }
 
+/// The bag with as many red, green and blue cubes as the parameters say.
+pub fn bag(params: &Params) -> CubeSet {
+    CubeSet(
+        ["red", "green", "blue"]
+            .into_iter()
+            .map(|colour| {
+                let count = params
+                    .parse(colour)
+                    .expect("integer parameters are checked when they are set");
+                (colour.to_owned(), count)
+            })
+            .collect(),
+    )
+}
+
 #[derive(Debug)]
 pub struct Game {
     id: usize,
-    hands: Vec<Hand>,
+    hands: Vec<CubeSet>,
 }
 
 static RE_GAME: Lazy<Regex> = Lazy::new(|| Regex::new(r"Game (?<id>\d+): (?<hands>.+)").unwrap());
@@ -54,52 +92,105 @@ impl FromStr for Game {
             id: captures["id"].parse()?,
             hands: captures["hands"]
                 .split("; ")
-                .map(Hand::from_str)
+                .map(CubeSet::from_str)
                 .collect::<Result<Vec<_>, _>>()?,
         })
     }
 }
 
+impl Game {
+    /// The fewest cubes of each colour that make every hand possible.
+    fn minimal_bag(&self) -> CubeSet {
+        let mut bag = CubeSet::default();
+        for hand in &self.hands {
+            for (colour, &count) in &hand.0 {
+                let minimum = bag.0.entry(colour.clone()).or_insert(0);
+                *minimum = count.max(*minimum);
+            }
+        }
+        bag
+    }
+
+    /// The first hand drawing more cubes of a colour than are in the bag.
+    fn violation(&self, bag: &CubeSet) -> Option<Violation> {
+        self.hands.iter().enumerate().find_map(|(draw, hand)| {
+            hand.0
+                .iter()
+                .find(|(colour, &count)| count > bag.get(colour))
+                .map(|(colour, &drawn)| Violation {
+                    draw,
+                    hand: hand.clone(),
+                    colour: colour.clone(),
+                    drawn,
+                    available: bag.get(colour),
+                })
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct Records(Vec<Game>);
 
 pub fn parse(input: &str) -> ParseResult<Records> {
-    Ok(Records(read_vec1::<Game>(input)?))
+    Ok(Records(read_lines::<Game>(input)?))
 }
 
-pub fn part1(records: &Records) -> PartOutput<usize> {
-    PartOutput {
-        answer: records
-            .0
-            .iter()
-            .filter_map(|game| {
-                if game
-                    .hands
-                    .iter()
-                    .any(|hand| hand.red > 12 || hand.green > 13 || hand.blue > 14)
-                {
-                    None
-                } else {
-                    Some(game.id)
-                }
-            })
-            .sum(),
+/// A hand that could not have been drawn from the bag.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// The index of the hand within the game.
+    pub draw: usize,
+    pub hand: CubeSet,
+    pub colour: String,
+    pub drawn: usize,
+    pub available: usize,
+}
+
+/// Whether a game was possible with a bag.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Feasibility {
+    pub id: usize,
+    pub minimal_bag: CubeSet,
+    pub violation: Option<Violation>,
+}
+
+impl Feasibility {
+    pub fn possible(&self) -> bool {
+        self.violation.is_none()
     }
 }
 
-pub fn part2(records: &Records) -> PartOutput<usize> {
-    PartOutput {
+pub fn feasibility(records: &Records, bag: &CubeSet) -> Vec<Feasibility> {
+    records
+        .0
+        .iter()
+        .map(|game| Feasibility {
+            id: game.id,
+            minimal_bag: game.minimal_bag(),
+            violation: game.violation(bag),
+        })
+        .collect()
+}
+
+pub fn part1(records: &Records, params: &Params, _context: &Context) -> PartResult<usize> {
+    Ok(PartOutput {
+        answer: feasibility(records, &bag(params))
+            .into_iter()
+            .filter(Feasibility::possible)
+            .map(|game| game.id)
+            .sum(),
+    })
+}
+
+pub fn part2(records: &Records, params: &Params, _context: &Context) -> PartResult<usize> {
+    let bag = bag(params);
+    Ok(PartOutput {
         answer: records
             .0
             .iter()
-            .map(|game| {
-                let min_red = game.hands.iter().map(|hand| hand.red).max().unwrap();
-                let min_green = game.hands.iter().map(|hand| hand.green).max().unwrap();
-                let min_blue = game.hands.iter().map(|hand| hand.blue).max().unwrap();
-                min_red * min_green * min_blue
-            })
+            .map(|game| game.minimal_bag().power(&bag))
             .sum(),
-    }
+    })
 }
 
 pub const DAY: Day<Records, usize, 1, 0, 0> = Day {
@@ -108,10 +199,102 @@ pub const DAY: Day<Records, usize, 1, 0, 0> = Day {
         "The sum of the IDs of the possible games is {answer}.",
         "The sum of the powers is {answer}.",
     ),
+    params: &[
+        Param {
+            name: "red",
+            description: "The number of red cubes in the bag",
+            kind: ParamKind::Integer,
+            default: "12",
+        },
+        Param {
+            name: "green",
+            description: "The number of green cubes in the bag",
+            kind: ParamKind::Integer,
+            default: "13",
+        },
+        Param {
+            name: "blue",
+            description: "The number of blue cubes in the bag",
+            kind: ParamKind::Integer,
+            default: "14",
+        },
+    ],
     calc: DayCalc {
         parse,
         part1,
         part2,
+        alternatives: &[],
     },
-    examples: Examples::single(include_str!("../../examples/day02.txt")),
+    examples: Examples::single(
+        Example::new(include_str!("../../examples/day02.txt")).answers("8", "2286"),
+    ),
 };
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_error_line() {
+        let e = parse("Game 1: 3 blue\nGame 2: 3 blue, 4 blue").unwrap_err();
+        assert_eq!(e.line(), Some(2));
+        assert_eq!(e.kind(), "Str");
+        assert_eq!(
+            e.to_string(),
+            "line 2: blue appears twice in 3 blue, 4 blue"
+        );
+    }
+
+    #[test]
+    fn test_feasibility() {
+        let records = parse(DAY.examples.common[0].input).unwrap();
+        let games = feasibility(&records, &bag(&Params::new(DAY.params)));
+        assert_eq!(
+            games[2],
+            Feasibility {
+                id: 3,
+                minimal_bag: "20 red, 13 green, 6 blue".parse().unwrap(),
+                violation: Some(Violation {
+                    draw: 0,
+                    hand: "8 green, 6 blue, 20 red".parse().unwrap(),
+                    colour: String::from("red"),
+                    drawn: 20,
+                    available: 12,
+                }),
+            }
+        );
+        let bag = "20 red, 13 green, 15 blue, 1 yellow".parse().unwrap();
+        assert!(feasibility(&records, &bag)
+            .iter()
+            .all(Feasibility::possible));
+    }
+
+    #[test]
+    fn test_power_of_missing_colour() {
+        let records = parse("Game 1: 3 blue, 4 red\nGame 2: 1 red, 2 green, 6 blue").unwrap();
+        assert_eq!(
+            part2(&records, &Params::new(DAY.params), &Context::default())
+                .unwrap()
+                .answer,
+            12
+        );
+    }
+
+    #[test]
+    fn test_bag_params() {
+        let records = parse(DAY.examples.common[0].input).unwrap();
+        let mut params = Params::new(DAY.params);
+        params.set("red", "20").unwrap();
+        assert_eq!(
+            part1(&records, &params, &Context::default())
+                .unwrap()
+                .answer,
+            1 + 2 + 3 + 5
+        );
+        assert!(params.set("green", "many").is_err());
+        assert!(params.set("yellow", "1").is_err());
+        assert_eq!(params.values().collect::<Vec<_>>(), [("red", "20")]);
+    }
+}
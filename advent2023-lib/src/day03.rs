@@ -1,7 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+
+use strum_macros::{Display, EnumString};
 
 use crate::parser::{read_map, FromChar};
-use crate::{Day, DayCalc, Examples, ParseError, ParseResult, PartOutput};
+use crate::{
+    Context, Day, DayCalc, Example, Examples, Param, ParamKind, Params, ParseError, ParseResult,
+    PartOutput, PartResult, SolveError,
+};
 
 enum SchematicCell {
     Blank,
@@ -21,114 +27,231 @@ impl FromChar for SchematicCell {
     }
 }
 
+/// A horizontal run of digits in the schematic.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Number {
+    pub value: usize,
+    pub row: usize,
+    pub columns: RangeInclusive<usize>,
+}
+
+impl Number {
+    /// Every position touching the number, including diagonally, and the
+    /// number itself.
+    fn surroundings(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (self.row - 1..=self.row + 1).flat_map(move |row| {
+            (self.columns.start() - 1..=self.columns.end() + 1).map(move |col| (col, row))
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct Part {
     symbol: char,
+    /// Indices into the numbers of the schematic.
     numbers: Vec<usize>,
 }
 
 #[derive(Debug)]
 pub struct Schematic {
+    numbers: Vec<Number>,
     parts: Vec<Part>,
 }
 
-fn scan_horizontal(map: &HashMap<(usize, usize), SchematicCell>, start: (usize, usize)) -> usize {
-    let mut pos = start;
-    assert!(matches!(map.get(&pos), Some(SchematicCell::Digit(_))));
-    loop {
-        pos = (pos.0 - 1, pos.1);
-        match map.get(&pos) {
-            Some(SchematicCell::Digit(_)) => continue,
-            Some(SchematicCell::Blank) | None => break,
-            Some(SchematicCell::Symbol(_)) => panic!(),
-        }
-    }
-    construct_right(map, (pos.0 + 1, pos.1))
-}
-
-fn construct_right(map: &HashMap<(usize, usize), SchematicCell>, start: (usize, usize)) -> usize {
-    let mut pos = start;
-    let Some(SchematicCell::Digit(first_char)) = map.get(&pos) else {
-        panic!()
-    };
-    let mut digits = vec![*first_char];
-    loop {
-        pos = (pos.0 + 1, pos.1);
-        match map.get(&pos) {
-            Some(SchematicCell::Digit(d)) => digits.push(*d),
-            Some(SchematicCell::Blank | SchematicCell::Symbol(_)) | None => break,
+/// Label every run of digits in the grid with its span.
+fn extract_numbers(map: &HashMap<(usize, usize), SchematicCell>) -> ParseResult<Vec<Number>> {
+    let width = map.keys().map(|&(col, _row)| col).max().unwrap_or(0);
+    let height = map.keys().map(|&(_col, row)| row).max().unwrap_or(0);
+    let mut numbers = Vec::new();
+    for row in 1..=height {
+        let mut run: Option<Number> = None;
+        // One past the last column, so that runs at the edge are closed.
+        for col in 1..=width + 1 {
+            match (map.get(&(col, row)), run.as_mut()) {
+                (Some(SchematicCell::Digit(d)), Some(number)) => {
+                    number.value = number
+                        .value
+                        .checked_mul(10)
+                        .and_then(|value| value.checked_add(usize::from(*d)))
+                        .ok_or_else(|| {
+                            ParseError::Str(String::from("a number is too large")).at_line(row)
+                        })?;
+                    number.columns = *number.columns.start()..=col;
+                },
+                (Some(SchematicCell::Digit(d)), None) => {
+                    run = Some(Number {
+                        value: usize::from(*d),
+                        row,
+                        columns: col..=col,
+                    })
+                },
+                (_, _) => numbers.extend(run.take()),
+            }
         }
     }
-    digits
-        .into_iter()
-        .fold(0_usize, |acc, elem| acc * 10 + usize::from(elem))
+    Ok(numbers)
 }
 
 pub fn parse(input: &str) -> ParseResult<Schematic> {
+    // Positions start at 1, so the surroundings of a number never underflow.
     let map = read_map::<SchematicCell>(input)?;
-    let mut parts = Vec::new();
-    for (&(x, y), cell) in &map {
-        if let SchematicCell::Symbol(symbol) = cell {
-            let mut numbers = Vec::new();
-            // top and bottom
-            for off_y in [y - 1, y + 1] {
-                if let Some(SchematicCell::Digit(_)) = map.get(&(x - 1, off_y)) {
-                    numbers.push(scan_horizontal(&map, (x - 1, off_y)));
-                    // Check if second number, e.g. 123 456
-                    //                                 p
-                    if let Some(SchematicCell::Blank) = map.get(&(x, off_y)) {
-                        if let Some(SchematicCell::Digit(_)) = map.get(&(x + 1, off_y)) {
-                            numbers.push(construct_right(&map, (x + 1, off_y)));
-                        }
-                    }
-                } else if let Some(SchematicCell::Digit(_)) = map.get(&(x, off_y)) {
-                    numbers.push(construct_right(&map, (x, off_y)));
-                } else if let Some(SchematicCell::Digit(_)) = map.get(&(x + 1, off_y)) {
-                    numbers.push(construct_right(&map, (x + 1, off_y)));
-                }
-            }
-            // left
-            if let Some(SchematicCell::Digit(_)) = map.get(&(x - 1, y)) {
-                numbers.push(scan_horizontal(&map, (x - 1, y)));
+    let numbers = extract_numbers(&map)?;
+    let mut symbol_numbers: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (index, number) in numbers.iter().enumerate() {
+        for position in number.surroundings() {
+            if let Some(SchematicCell::Symbol(_)) = map.get(&position) {
+                symbol_numbers.entry(position).or_default().push(index);
             }
-            // right
-            if let Some(SchematicCell::Digit(_)) = map.get(&(x + 1, y)) {
-                numbers.push(construct_right(&map, (x + 1, y)));
-            }
-            parts.push(Part {
+        }
+    }
+    let parts = map
+        .iter()
+        .filter_map(|(position, cell)| match cell {
+            SchematicCell::Symbol(symbol) => Some(Part {
                 symbol: *symbol,
-                numbers,
-            });
+                numbers: symbol_numbers.remove(position).unwrap_or_default(),
+            }),
+            _ => None,
+        })
+        .collect();
+    Ok(Schematic { numbers, parts })
+}
+
+impl Schematic {
+    /// Split the numbers into part numbers, which are adjacent to a symbol,
+    /// and numbers adjacent to no symbol.
+    pub fn part_numbers(&self) -> (Vec<&Number>, Vec<&Number>) {
+        let mut attached = vec![false; self.numbers.len()];
+        for part in &self.parts {
+            for &index in &part.numbers {
+                attached[index] = true;
+            }
         }
+        let (part_numbers, unattached): (Vec<_>, Vec<_>) = self
+            .numbers
+            .iter()
+            .zip(attached)
+            .partition(|(_number, attached)| *attached);
+        (
+            part_numbers.into_iter().map(|(number, _)| number).collect(),
+            unattached.into_iter().map(|(number, _)| number).collect(),
+        )
     }
-    Ok(Schematic { parts })
 }
 
-pub fn part1(schematic: &Schematic) -> PartOutput<usize> {
+pub fn part1(schematic: &Schematic, _params: &Params, _context: &Context) -> PartResult<usize> {
     log::info!("Schematic: {:#?}", schematic);
-    PartOutput {
-        answer: schematic
-            .parts
+    let (part_numbers, unattached) = schematic.part_numbers();
+    log::debug!("numbers adjacent to no symbol: {:?}", unattached);
+    Ok(PartOutput {
+        answer: part_numbers.into_iter().map(|number| number.value).sum(),
+    })
+}
+
+/// How the numbers adjacent to a gear are combined into its ratio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum Reduction {
+    Product,
+    Sum,
+}
+
+/// Which parts are gears: a part with one of the `symbols` that is adjacent
+/// to exactly `adjacent` numbers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GearRule {
+    pub symbols: Vec<char>,
+    pub adjacent: usize,
+    pub reduction: Reduction,
+}
+
+impl Default for GearRule {
+    fn default() -> Self {
+        Self {
+            symbols: vec!['*'],
+            adjacent: 2,
+            reduction: Reduction::Product,
+        }
+    }
+}
+
+impl GearRule {
+    /// The rule with the symbols, the number of adjacent numbers and the
+    /// reduction given by the parameters.
+    pub fn from_params(params: &Params) -> Result<Self, SolveError> {
+        let reduction = params.parse("gear_reduction").map_err(|_| {
+            SolveError::Str(format!(
+                "unknown gear_reduction {}, choose from: product, sum",
+                params.get("gear_reduction")
+            ))
+        })?;
+        Ok(Self {
+            symbols: params.get("gear_symbols").chars().collect(),
+            adjacent: params
+                .parse("gear_numbers")
+                .expect("integer parameters are checked when they are set"),
+            reduction,
+        })
+    }
+
+    fn ratio(&self, schematic: &Schematic, part: &Part) -> Option<usize> {
+        if !self.symbols.contains(&part.symbol) || part.numbers.len() != self.adjacent {
+            return None;
+        }
+        let values = part
+            .numbers
             .iter()
-            .map(|part| part.numbers.iter().sum::<usize>())
-            .sum(),
+            .map(|&index| schematic.numbers[index].value);
+        Some(match self.reduction {
+            Reduction::Product => values.product(),
+            Reduction::Sum => values.sum(),
+        })
     }
 }
 
-pub fn part2(schematic: &Schematic) -> PartOutput<usize> {
-    PartOutput {
-        answer: schematic
-            .parts
+pub fn gear_ratio_sum(schematic: &Schematic, rule: &GearRule) -> usize {
+    schematic
+        .parts
+        .iter()
+        .filter_map(|part| rule.ratio(schematic, part))
+        .sum()
+}
+
+/// The numbers touched by every symbol of one kind.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SymbolStatistics {
+    pub symbol: char,
+    /// How often the symbol appears in the schematic.
+    pub count: usize,
+    /// How many numbers the symbols touch, counting a number once per symbol.
+    pub numbers: usize,
+    pub sum: usize,
+}
+
+pub fn symbol_statistics(schematic: &Schematic) -> Vec<SymbolStatistics> {
+    let mut statistics = BTreeMap::<char, SymbolStatistics>::new();
+    for part in &schematic.parts {
+        let entry = statistics
+            .entry(part.symbol)
+            .or_insert_with(|| SymbolStatistics {
+                symbol: part.symbol,
+                ..Default::default()
+            });
+        entry.count += 1;
+        entry.numbers += part.numbers.len();
+        entry.sum += part
+            .numbers
             .iter()
-            .filter_map(|part| {
-                if part.symbol != '*' || part.numbers.len() != 2 {
-                    None
-                } else {
-                    Some(part.numbers[0] * part.numbers[1])
-                }
-            })
-            .sum(),
+            .map(|&index| schematic.numbers[index].value)
+            .sum::<usize>();
     }
+    statistics.into_values().collect()
+}
+
+pub fn part2(schematic: &Schematic, params: &Params, _context: &Context) -> PartResult<usize> {
+    Ok(PartOutput {
+        answer: gear_ratio_sum(schematic, &GearRule::from_params(params)?),
+    })
 }
 
 pub const DAY: Day<Schematic, usize, 1, 0, 0> = Day {
@@ -137,10 +260,134 @@ pub const DAY: Day<Schematic, usize, 1, 0, 0> = Day {
         "The sum of all of the part numbers in the engine schematic is {answer}.",
         "The sum of all of the gear ratios in the engine schematic is {answer}.",
     ),
+    params: &[
+        Param {
+            name: "gear_symbols",
+            description: "The symbols that can be gears",
+            kind: ParamKind::Text,
+            default: "*",
+        },
+        Param {
+            name: "gear_numbers",
+            description: "How many numbers a gear is adjacent to",
+            kind: ParamKind::Integer,
+            default: "2",
+        },
+        Param {
+            name: "gear_reduction",
+            description: "How a gear's numbers make its ratio: product or sum",
+            kind: ParamKind::Text,
+            default: "product",
+        },
+    ],
     calc: DayCalc {
         parse,
         part1,
         part2,
+        alternatives: &[],
     },
-    examples: Examples::single(include_str!("../../examples/day03.txt")),
+    examples: Examples::single(
+        Example::new(include_str!("../../examples/day03.txt")).answers("4361", "467835"),
+    ),
 };
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_edges() {
+        let schematic = parse("12.3\n..*.\n45.6").unwrap();
+        assert_eq!(
+            part1(&schematic, &Params::new(DAY.params), &Context::default())
+                .unwrap()
+                .answer,
+            12 + 3 + 45 + 6
+        );
+        assert!(schematic.part_numbers().1.is_empty());
+    }
+
+    #[test]
+    fn test_number_too_large() {
+        let e = parse(&format!("1.\n.*\n{}", "9".repeat(30))).unwrap_err();
+        assert_eq!(e.line(), Some(3));
+    }
+
+    #[test]
+    fn test_shared_number() {
+        // 7 touches both symbols but is a single part number.
+        let schematic = parse("*.*\n.7.\n...").unwrap();
+        assert_eq!(
+            part1(&schematic, &Params::new(DAY.params), &Context::default())
+                .unwrap()
+                .answer,
+            7
+        );
+        let schematic = parse("*...\n.78.\n...*\n1...").unwrap();
+        assert_eq!(
+            part1(&schematic, &Params::new(DAY.params), &Context::default())
+                .unwrap()
+                .answer,
+            78
+        );
+        assert_eq!(
+            schematic.part_numbers().1,
+            vec![&Number {
+                value: 1,
+                row: 4,
+                columns: 1..=1
+            }]
+        );
+    }
+
+    #[test]
+    fn test_gear_between_numbers() {
+        let schematic = parse("123*456").unwrap();
+        assert_eq!(
+            part2(&schematic, &Params::new(DAY.params), &Context::default())
+                .unwrap()
+                .answer,
+            123 * 456
+        );
+    }
+
+    #[test]
+    fn test_gear_rule() {
+        let schematic = parse(DAY.examples.common[0].input).unwrap();
+        let rule = GearRule {
+            symbols: vec!['*', '#', '+', '$'],
+            adjacent: 1,
+            reduction: Reduction::Sum,
+        };
+        assert_eq!(gear_ratio_sum(&schematic, &rule), 633 + 617 + 592 + 664);
+
+        let mut params = Params::new(DAY.params);
+        params.set("gear_symbols", "*#+$").unwrap();
+        params.set("gear_numbers", "1").unwrap();
+        params.set("gear_reduction", "sum").unwrap();
+        assert_eq!(GearRule::from_params(&params).unwrap(), rule);
+        params.set("gear_reduction", "mean").unwrap();
+        assert!(GearRule::from_params(&params).is_err());
+    }
+
+    #[test]
+    fn test_symbol_statistics() {
+        let schematic = parse(DAY.examples.common[0].input).unwrap();
+        let statistics = symbol_statistics(&schematic);
+        assert_eq!(
+            statistics.iter().map(|s| s.symbol).collect::<String>(),
+            "#$*+"
+        );
+        assert_eq!(
+            statistics[2],
+            SymbolStatistics {
+                symbol: '*',
+                count: 3,
+                numbers: 5,
+                sum: 467 + 35 + 617 + 755 + 598,
+            }
+        );
+    }
+}
@@ -1,11 +1,15 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Bound::{Excluded, Included};
 use std::str::FromStr;
 
 use once_cell::sync::Lazy;
 use regex::Regex;
 
-use crate::parser::read_vec1;
-use crate::{Day, DayCalc, Examples, ParseError, ParseResult, PartOutput};
+use crate::parser::read_lines;
+use crate::{
+    Context, Day, DayCalc, Example, Examples, Params, ParseError, ParseResult, PartOutput,
+    PartResult,
+};
 
 #[derive(Debug)]
 struct Card {
@@ -61,36 +65,74 @@ impl FromStr for Card {
     }
 }
 
+/// The cards in the pile, by id.
 #[derive(Debug)]
-pub struct PileOfColourfulCards(Vec<Card>);
+pub struct PileOfColourfulCards(BTreeMap<usize, Card>);
 
 pub fn parse(input: &str) -> ParseResult<PileOfColourfulCards> {
-    Ok(PileOfColourfulCards(read_vec1::<Card>(input)?))
+    let mut pile = BTreeMap::new();
+    for card in read_lines::<Card>(input)? {
+        let id = card.id;
+        if pile.insert(id, card).is_some() {
+            return Err(ParseError::Str(format!("card {} appears twice", id)));
+        }
+    }
+    Ok(PileOfColourfulCards(pile))
 }
 
-pub fn part1(pile_of_cards: &PileOfColourfulCards) -> PartOutput<usize> {
-    PartOutput {
-        answer: pile_of_cards.0.iter().map(|c| c.points()).sum(),
-    }
+pub fn part1(
+    pile_of_cards: &PileOfColourfulCards,
+    _params: &Params,
+    _context: &Context,
+) -> PartResult<usize> {
+    Ok(PartOutput {
+        answer: pile_of_cards.0.values().map(|c| c.points()).sum(),
+    })
+}
+
+/// How many of one card we end up with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardCopies {
+    pub id: usize,
+    pub matching: usize,
+    /// The original card and every copy won.
+    pub copies: usize,
 }
 
-pub fn part2(pile_of_cards: &PileOfColourfulCards) -> PartOutput<usize> {
-    let mut card_counter = HashMap::<usize, usize>::new();
-    // We assume pile_of_cards is a sorted list
-    for card in &pile_of_cards.0 {
-        let additional_count = card_counter.entry(card.id).or_insert(1).to_owned();
+/// Play out the cascade of won copies, card by card in order of id.
+///
+/// A card with `n` matching numbers wins a copy of each of the cards with
+/// the next `n` ids, ignoring ids that are not in the pile.
+pub fn copies(pile_of_cards: &PileOfColourfulCards) -> Vec<CardCopies> {
+    let mut copies: BTreeMap<usize, usize> = pile_of_cards.0.keys().map(|&id| (id, 1)).collect();
+    for (&id, card) in &pile_of_cards.0 {
+        let count = copies[&id];
         let matching = card.count_matching();
-        for idx in 1..=matching {
-            card_counter
-                .entry(card.id + idx)
-                .and_modify(|count| *count += additional_count)
-                .or_insert(1 + additional_count);
+        let won = (Excluded(id), Included(id.saturating_add(matching)));
+        for (_won_id, won_count) in copies.range_mut(won) {
+            *won_count += count;
         }
-        log::debug!("state: {:#?}", card_counter);
-    }
-    PartOutput {
-        answer: card_counter.values().sum(),
+        log::debug!("state: {:#?}", copies);
     }
+    pile_of_cards
+        .0
+        .iter()
+        .map(|(&id, card)| CardCopies {
+            id,
+            matching: card.count_matching(),
+            copies: copies[&id],
+        })
+        .collect()
+}
+
+pub fn part2(
+    pile_of_cards: &PileOfColourfulCards,
+    _params: &Params,
+    _context: &Context,
+) -> PartResult<usize> {
+    Ok(PartOutput {
+        answer: copies(pile_of_cards).iter().map(|card| card.copies).sum(),
+    })
 }
 
 pub const DAY: Day<PileOfColourfulCards, usize, 1, 0, 0> = Day {
@@ -99,10 +141,79 @@ pub const DAY: Day<PileOfColourfulCards, usize, 1, 0, 0> = Day {
         "The scratchcards are worth {answer} points.",
         "We end up with {answer} scratchcards.",
     ),
+    params: &[],
     calc: DayCalc {
         parse,
         part1,
         part2,
+        alternatives: &[],
     },
-    examples: Examples::single(include_str!("../../examples/day04.txt")),
+    examples: Examples::single(
+        Example::new(include_str!("../../examples/day04.txt")).answers("13", "30"),
+    ),
 };
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_unsorted() {
+        let mut lines: Vec<&str> = DAY.examples.common[0].input.lines().collect();
+        lines.reverse();
+        let pile = parse(&lines.join("\n")).unwrap();
+        assert_eq!(
+            part2(&pile, &Params::new(DAY.params), &Context::default())
+                .unwrap()
+                .answer,
+            30
+        );
+    }
+
+    #[test]
+    fn test_last_id() {
+        let pile = parse(&format!("Card {}: 1 2 | 1 2", usize::MAX)).unwrap();
+        assert_eq!(
+            part2(&pile, &Params::new(DAY.params), &Context::default())
+                .unwrap()
+                .answer,
+            1
+        );
+    }
+
+    #[test]
+    fn test_past_the_end() {
+        // Card 2 wins copies of cards 3 and 4, which are not in the pile.
+        let pile = parse("Card 1: 1 2 | 1 3\nCard 2: 1 2 | 1 2").unwrap();
+        assert_eq!(
+            copies(&pile),
+            vec![
+                CardCopies {
+                    id: 1,
+                    matching: 1,
+                    copies: 1
+                },
+                CardCopies {
+                    id: 2,
+                    matching: 2,
+                    copies: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gaps() {
+        // Card 1 wins a copy of card 3, card 2 is missing.
+        let pile = parse("Card 1: 1 2 | 1 2\nCard 3: 1 | 1\nCard 4: 1 | 2").unwrap();
+        assert_eq!(
+            part2(&pile, &Params::new(DAY.params), &Context::default())
+                .unwrap()
+                .answer,
+            1 + 2 + 3
+        );
+        assert!(parse("Card 1: 1 | 1\nCard 1: 1 | 1").is_err());
+    }
+}
@@ -3,7 +3,10 @@ use std::num::ParseIntError;
 use std::ops::RangeInclusive;
 use std::str::FromStr;
 
-use crate::{Day, DayCalc, Examples, ParseError, ParseResult, PartOutput};
+use crate::{
+    Context, Day, DayCalc, Example, Examples, Params, ParseError, ParseResult, PartOutput,
+    PartResult, SolveError,
+};
 
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub struct Range {
@@ -16,11 +19,16 @@ impl FromStr for Range {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut split = s.split_whitespace();
-        let destination = split.next().unwrap().parse()?;
-        let source = split.next().unwrap().parse()?;
-        let length = split.next().unwrap().parse()?;
-        assert!(split.next().is_none());
+        let numbers = s
+            .split_whitespace()
+            .map(usize::from_str)
+            .collect::<Result<Vec<usize>, ParseIntError>>()?;
+        let [destination, source, length] = numbers[..] else {
+            return Err(ParseError::Str(format!(
+                "expected a destination, a source and a length, not {}",
+                s
+            )));
+        };
         Ok(Self {
             destination,
             source,
@@ -55,28 +63,16 @@ pub struct Map {
     ranges: Vec<Range>,
 }
 
-impl FromStr for Map {
-    type Err = ParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut lines = s.lines();
-        let (from, to) = {
-            let (naming, map) = lines.next().unwrap().split_once(' ').unwrap();
-            assert_eq!(map, "map:");
-            naming.split_once("-to-").unwrap()
-        };
-        let ranges = lines
-            .map(Range::from_str)
-            .collect::<Result<Vec<Range>, ParseError>>()?;
-        Ok(Self {
-            from: from.to_owned(),
-            to: to.to_owned(),
-            ranges,
-        })
+impl Map {
+    /// The categories a map converts between, from a line like
+    /// `seed-to-soil map:`.
+    fn parse_header(line: &str) -> ParseResult<(String, String)> {
+        line.strip_suffix(" map:")
+            .and_then(|naming| naming.split_once("-to-"))
+            .map(|(from, to)| (from.to_owned(), to.to_owned()))
+            .ok_or_else(|| ParseError::Str(format!("expected a map, not {}", line)))
     }
-}
 
-impl Map {
     fn convert(&self, from: usize) -> usize {
         for range in &self.ranges {
             if let Some(to) = range.convert(from) {
@@ -109,29 +105,62 @@ impl FromStr for Almanac {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut sections = s.trim().split("\n\n");
+        let mut lines = (1..).zip(s.lines());
+        let seeds = lines.next().ok_or(ParseError::Empty)?.1;
         let (start, ranges) = {
-            let mut parts = sections.next().unwrap().split_whitespace();
-            assert_eq!(parts.next(), Some("seeds:"));
-            let number = parts
+            let Some(seeds) = seeds.strip_prefix("seeds:") else {
+                return Err(
+                    ParseError::Str(format!("expected the seeds, not {}", seeds)).at_line(1),
+                );
+            };
+            let number = seeds
+                .split_whitespace()
                 .map(usize::from_str)
-                .collect::<Result<Vec<usize>, ParseIntError>>()?;
+                .collect::<Result<Vec<usize>, ParseIntError>>()
+                .map_err(|e| ParseError::from(e).at_line(1))?;
+            if number.len() % 2 != 0 {
+                return Err(ParseError::Str(String::from(
+                    "the seeds should come in pairs of a start and a length",
+                ))
+                .at_line(1));
+            }
             (
                 number.iter().cloned().collect(),
                 number
                     .chunks(2)
-                    .map(|chunk| RangeInclusive::new(chunk[0], chunk[0] + chunk[1]))
+                    .map(|chunk| RangeInclusive::new(chunk[0], chunk[0].saturating_add(chunk[1])))
                     .collect(),
             )
         };
-        let maps = sections
-            .map(Map::from_str)
-            .collect::<Result<Vec<Map>, ParseError>>()?;
-        assert_eq!(maps.first().unwrap().from, "seed");
-        for window in maps.windows(2) {
-            assert_eq!(window[0].to, window[1].from);
+        // Each map is a header and its ranges, after a blank line, and
+        // converts from what the map before it converts to.
+        let mut maps: Vec<Map> = Vec::new();
+        while let Some((line, header)) = lines.find(|(_line, text)| !text.trim().is_empty()) {
+            let at_line = |e: ParseError| e.at_line(line);
+            let (from, to) = Map::parse_header(header).map_err(at_line)?;
+            let ranges = lines
+                .by_ref()
+                .take_while(|(_line, text)| !text.trim().is_empty())
+                .map(|(line, text)| text.parse().map_err(|e: ParseError| e.at_line(line)))
+                .collect::<ParseResult<Vec<Range>>>()?;
+            let map = Map { from, to, ranges };
+            let expected = maps.last().map_or("seed", |last| last.to.as_str());
+            if map.from != expected {
+                return Err(at_line(ParseError::Str(format!(
+                    "expected a map from {}, not from {}",
+                    expected, map.from
+                ))));
+            }
+            maps.push(map);
+        }
+        let end = maps.last().map_or("seed", |last| last.to.as_str());
+        if end != "location" {
+            return Err(ParseError::Str(format!(
+                "the maps should reach location, but end at {}",
+                end
+            ))
+            .at_line(s.lines().count()));
         }
-        assert_eq!(maps.last().unwrap().to, "location");
         Ok(Self {
             start,
             ranges,
@@ -165,19 +194,27 @@ pub fn parse(input: &str) -> ParseResult<Almanac> {
     input.parse()
 }
 
-pub fn part1(almanac: &Almanac) -> PartOutput<usize> {
+pub fn part1(almanac: &Almanac, _params: &Params, _context: &Context) -> PartResult<usize> {
     let min_location = almanac
         .start
         .iter()
         .map(|&seed| almanac.convert(seed))
         .min()
-        .unwrap();
-    PartOutput {
+        .ok_or(SolveError::Empty)?;
+    Ok(PartOutput {
         answer: min_location,
-    }
+    })
 }
 
-pub fn part2(almanac: &Almanac) -> PartOutput<usize> {
+pub fn part2(almanac: &Almanac, _params: &Params, context: &Context) -> PartResult<usize> {
+    // The location of the start of any range is as far as the search goes.
+    let bound = almanac
+        .ranges
+        .iter()
+        .filter(|range| !range.is_empty())
+        .map(|range| almanac.convert(*range.start()))
+        .min()
+        .ok_or(SolveError::Empty)?;
     let mut location = 1;
     loop {
         log::debug!(
@@ -193,11 +230,12 @@ pub fn part2(almanac: &Almanac) -> PartOutput<usize> {
             break;
         }
         location += 1;
-        if location % 1000000 == 0 {
-            log::info!("location {}", location);
+        if location % 1000 == 0 {
+            context.check()?;
+            context.report(location as u64, Some(bound as u64));
         }
     }
-    PartOutput { answer: location }
+    Ok(PartOutput { answer: location })
 }
 
 pub const DAY: Day<Almanac, usize, 1, 0, 0> = Day {
@@ -206,19 +244,26 @@ pub const DAY: Day<Almanac, usize, 1, 0, 0> = Day {
         "The lowest location number is {answer}.",
         "The lowest location number given ranges is {answer}.",
     ),
+    params: &[],
     calc: DayCalc {
         parse,
         part1,
         part2,
+        alternatives: &[],
     },
-    examples: Examples::single(include_str!("../../examples/day05.txt")),
+    examples: Examples::single(
+        Example::new(include_str!("../../examples/day05.txt")).answers("35", "46"),
+    ),
 };
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+
     use test_log::test;
 
     use super::*;
+    use crate::Progress;
 
     #[test]
     fn test_range() {
@@ -260,4 +305,78 @@ mod tests {
         assert_eq!(map.invert_convert(10), [10, 20, 100].into());
         assert_eq!(map.invert_convert(11), [11, 21, 101].into());
     }
+
+    #[test]
+    fn test_no_seeds() {
+        let almanac = parse("seeds:\n\nseed-to-location map:\n1 2 3").unwrap();
+        let params = Params::new(DAY.params);
+        assert!(matches!(
+            part1(&almanac, &params, &Context::default()),
+            Err(SolveError::Empty)
+        ));
+        assert!(matches!(
+            part2(&almanac, &params, &Context::default()),
+            Err(SolveError::Empty)
+        ));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let almanac = parse("seeds: 5000 1\n\nseed-to-location map:\n1 2 3").unwrap();
+        let params = Params::new(DAY.params);
+        let context = Context::default();
+        assert_eq!(part2(&almanac, &params, &context).unwrap().answer, 5000);
+        context.clone().cancel();
+        assert!(matches!(
+            part2(&almanac, &params, &context),
+            Err(SolveError::Cancelled)
+        ));
+    }
+
+    #[test]
+    fn test_progress() {
+        let almanac = parse("seeds: 5000 1 7000 1\n\nseed-to-location map:\n1 2 3").unwrap();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let context = {
+            let reports = Arc::clone(&reports);
+            Context::default().with_progress(move |progress| reports.lock().unwrap().push(progress))
+        };
+        part2(&almanac, &Params::new(DAY.params), &context).unwrap();
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 5);
+        assert_eq!(
+            reports[4],
+            Progress {
+                done: 5000,
+                total: Some(5000)
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_error_line() {
+        for (input, line) in [
+            ("x", Some(1)),
+            ("1 2", Some(1)),
+            ("seeds: 1 2 3", Some(1)),
+            ("seeds: 1 2\n", Some(1)),
+            ("seeds: 1 2\n\nseed-to-soil map:\n1 2", Some(4)),
+            (
+                "seeds: 1 2\n\nseed-to-soil map:\n1 2 3\n\nwater-to-location map:",
+                Some(6),
+            ),
+            ("seeds: 1 2\n\nseed-to-soil map:\n1 2 3", Some(4)),
+            (include_str!("../../examples/day06.txt"), Some(1)),
+            ("", None),
+        ] {
+            let e = parse(input).unwrap_err();
+            assert_eq!(e.line(), line, "{:?} gave {}", input, e);
+        }
+        assert_eq!(
+            parse("seeds: 1 2\n\nseed-to-location map:\n1 2")
+                .unwrap_err()
+                .to_string(),
+            "line 4: expected a destination, a source and a length, not 1 2"
+        );
+    }
 }
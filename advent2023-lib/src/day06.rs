@@ -1,8 +1,10 @@
-use std::num::ParseIntError;
 use std::ops::{Neg, RangeInclusive};
 use std::str::FromStr;
 
-use crate::{Day, DayCalc, Examples, ParseError, ParseResult, PartOutput};
+use crate::{
+    Context, Day, DayCalc, Example, Examples, Params, ParseError, ParseResult, PartOutput,
+    PartResult, SolveError,
+};
 
 #[derive(Debug)]
 pub struct Race {
@@ -11,26 +13,35 @@ pub struct Race {
 }
 
 impl Race {
-    fn from_parsed(
-        time: Result<usize, ParseIntError>,
-        distance: Result<usize, ParseIntError>,
-    ) -> Result<Self, ParseIntError> {
-        let time = match time {
-            Ok(time) => time,
-            Err(err) => return Err(err),
-        };
-        let distance = match distance {
-            Ok(distance) => distance,
-            Err(err) => return Err(err),
-        };
-        Ok(Self { time, distance })
-    }
-    fn winning_range(&self) -> RangeInclusive<usize> {
+    fn winning_range(&self) -> Result<RangeInclusive<usize>, SolveError> {
         let a = 1;
-        let b = i32::try_from(self.time).unwrap().neg();
+        let b = (self.time as f64).neg();
         let c = self.distance as f64;
-        let roots = solve_quadratic(a, b, c);
-        (roots[0] + 0.000000001).ceil() as usize..=(roots[1] - 0.000000001).floor() as usize
+        let roots = solve_quadratic(a, b, c).ok_or_else(|| {
+            SolveError::Str(format!(
+                "a race of {} ms cannot beat the record of {} mm",
+                self.time, self.distance
+            ))
+        })?;
+        // The roots are close, but not exact for long races, so settle the ends
+        // on the hold times that beat the record.
+        let beats =
+            |hold: usize| (hold as u128) * ((self.time - hold) as u128) > self.distance as u128;
+        let mut start = (roots[0].ceil() as usize).min(self.time);
+        while start > 0 && beats(start - 1) {
+            start -= 1;
+        }
+        while start <= self.time && !beats(start) {
+            start += 1;
+        }
+        let mut end = (roots[1].floor() as usize).min(self.time);
+        while end < self.time && beats(end + 1) {
+            end += 1;
+        }
+        while end > start && !beats(end) {
+            end -= 1;
+        }
+        Ok(start..=end)
     }
 }
 
@@ -40,33 +51,57 @@ pub struct Competition {
     kerning_race: Race,
 }
 
+/// The numbers on a line, after the label it should start with.
+fn labelled<'a>(line: &'a str, label: &str) -> ParseResult<&'a str> {
+    line.strip_prefix(label)
+        .ok_or_else(|| ParseError::Str(format!("expected {}, not {}", label, line)))
+}
+
 impl FromStr for Competition {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let [times, distances] = {
-            let mut lines = s.lines();
-            [lines.next().unwrap(), lines.next().unwrap()]
-        };
-        Ok(Self {
-            races: times
+        let mut lines = s.lines();
+        let times = lines.next().ok_or(ParseError::Empty)?;
+        let times = labelled(times, "Time:").map_err(|e| e.at_line(1))?;
+        let distances = lines.next().ok_or_else(|| {
+            ParseError::Str(String::from(
+                "the times should be followed by the distances",
+            ))
+            .at_line(1)
+        })?;
+        let distances = labelled(distances, "Distance:").map_err(|e| e.at_line(2))?;
+        let numbers = |numbers: &str, line: usize| {
+            numbers
                 .split_whitespace()
-                .skip(1)
                 .map(usize::from_str)
-                .zip(distances.split_whitespace().skip(1).map(usize::from_str))
-                .map(|(time, distance)| Race::from_parsed(time, distance))
-                .collect::<Result<Vec<_>, _>>()?,
+                .collect::<Result<Vec<usize>, _>>()
+                .map_err(|e| ParseError::from(e).at_line(line))
+        };
+        let kerned = |numbers: &str, line: usize| {
+            numbers
+                .split_whitespace()
+                .collect::<String>()
+                .parse()
+                .map_err(|e| ParseError::from(e).at_line(line))
+        };
+        let race_times = numbers(times, 1)?;
+        let race_distances = numbers(distances, 2)?;
+        if race_times.len() != race_distances.len() {
+            return Err(
+                ParseError::Str(String::from("there should be a distance for each time"))
+                    .at_line(2),
+            );
+        }
+        Ok(Self {
+            races: race_times
+                .into_iter()
+                .zip(race_distances)
+                .map(|(time, distance)| Race { time, distance })
+                .collect(),
             kerning_race: Race {
-                time: times
-                    .split_whitespace()
-                    .skip(1)
-                    .collect::<String>()
-                    .parse()?,
-                distance: distances
-                    .split_whitespace()
-                    .skip(1)
-                    .collect::<String>()
-                    .parse()?,
+                time: kerned(times, 1)?,
+                distance: kerned(distances, 2)?,
             },
         })
     }
@@ -76,20 +111,22 @@ pub fn parse(input: &str) -> ParseResult<Competition> {
     Competition::from_str(input)
 }
 
-fn solve_quadratic<A: Into<f64>, B: Into<f64>, C: Into<f64>>(a: A, b: B, c: C) -> [f64; 2] {
+/// The two real roots, or `None` if there are not two.
+fn solve_quadratic<A: Into<f64>, B: Into<f64>, C: Into<f64>>(a: A, b: B, c: C) -> Option<[f64; 2]> {
     let a: f64 = a.into();
     let b: f64 = b.into();
     let c: f64 = c.into();
     let discriminant_squared = b.powi(2) - (4.0 * a * c);
-    assert!(discriminant_squared > 0.0);
+    if discriminant_squared <= 0.0 || !discriminant_squared.is_finite() {
+        return None;
+    }
     let discriminant = discriminant_squared.sqrt();
-    assert!(discriminant.is_normal());
     let roots = [
         (b.neg() - discriminant) / (2.0 * a),
         (b.neg() + discriminant) / (2.0 * a),
     ];
     log::debug!("{} {} {} roots: {:?}", a, b, c, roots);
-    roots
+    Some(roots)
 }
 
 /// T: competition time
@@ -110,25 +147,25 @@ fn solve_quadratic<A: Into<f64>, B: Into<f64>, C: Into<f64>>(a: A, b: B, c: C) -
 ///
 /// This is the quadratic equation where
 /// a = 1, b = -T, and c = D
-pub fn part1(competition: &Competition) -> PartOutput<usize> {
+pub fn part1(competition: &Competition, _params: &Params, _context: &Context) -> PartResult<usize> {
     log::debug!("competition: {:?}", competition);
-    PartOutput {
+    Ok(PartOutput {
         answer: competition
             .races
             .iter()
             .map(|race| {
-                let range = race.winning_range();
+                let range = race.winning_range()?;
                 log::debug!("range: {:?}", range);
-                range.count()
+                Ok(range.count())
             })
-            .product(),
-    }
+            .product::<Result<usize, SolveError>>()?,
+    })
 }
 
-pub fn part2(competition: &Competition) -> PartOutput<usize> {
-    PartOutput {
-        answer: competition.kerning_race.winning_range().count(),
-    }
+pub fn part2(competition: &Competition, _params: &Params, _context: &Context) -> PartResult<usize> {
+    Ok(PartOutput {
+        answer: competition.kerning_race.winning_range()?.count(),
+    })
 }
 
 pub const DAY: Day<Competition, usize, 1, 0, 0> = Day {
@@ -137,10 +174,62 @@ pub const DAY: Day<Competition, usize, 1, 0, 0> = Day {
         "The product of the ways to beat the record are {answer}.",
         "There are {answer} ways to beat the race.",
     ),
+    params: &[],
     calc: DayCalc {
         parse,
         part1,
         part2,
+        alternatives: &[],
     },
-    examples: Examples::single(include_str!("../../examples/day06.txt")),
+    examples: Examples::single(
+        Example::new(include_str!("../../examples/day06.txt")).answers("288", "71503"),
+    ),
 };
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_error_line() {
+        for (input, line) in [
+            ("x", Some(1)),
+            ("Time: 7 15", Some(1)),
+            ("Time: 7 15\nDistance: 9", Some(2)),
+            ("Time: 7 x\nDistance: 9 40", Some(1)),
+            ("Time: 7 15\nSpeed: 9 40", Some(2)),
+            (include_str!("../../examples/day05.txt"), Some(1)),
+            ("", None),
+        ] {
+            let e = parse(input).unwrap_err();
+            assert_eq!(e.line(), line, "{:?} gave {}", input, e);
+        }
+    }
+
+    #[test]
+    fn test_unwinnable_race() {
+        let params = Params::new(DAY.params);
+        let competition = parse("Time: 1\nDistance: 5").unwrap();
+        assert!(part1(&competition, &params, &Context::default()).is_err());
+        assert!(part2(&competition, &params, &Context::default()).is_err());
+        let competition = parse("Time: 1\nDistance: 0").unwrap();
+        assert_eq!(
+            part1(&competition, &params, &Context::default())
+                .unwrap()
+                .answer,
+            0
+        );
+    }
+
+    #[test]
+    fn test_long_race() {
+        let params = Params::new(DAY.params);
+        let competition = parse("Time: 3000000000\nDistance: 5").unwrap();
+        let answer = part1(&competition, &params, &Context::default())
+            .unwrap()
+            .answer;
+        assert_eq!(answer, 3000000000 - 1);
+    }
+}
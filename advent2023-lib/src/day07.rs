@@ -2,11 +2,14 @@ use std::collections::HashMap;
 use std::fmt::Display;
 use std::str::FromStr;
 
-use crate::{Day, DayCalc, Examples, ParseError, ParseResult, PartOutput};
+use crate::parser::FromChar;
+use crate::{
+    Context, Day, DayCalc, Example, Examples, Implementation, Params, ParseError, ParseResult,
+    PartOutput, PartResult, SolveError,
+};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum Card {
-    Joker,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Card {
     Two,
     Three,
     Four,
@@ -22,9 +25,11 @@ enum Card {
     A,
 }
 
-impl Card {
-    fn from_char(c: char) -> Self {
-        match c {
+impl FromChar for Card {
+    type Err = ParseError;
+
+    fn from_char(c: char) -> Result<Self, Self::Err> {
+        Ok(match c {
             '2' => Self::Two,
             '3' => Self::Three,
             '4' => Self::Four,
@@ -38,12 +43,36 @@ impl Card {
             'Q' => Self::Q,
             'K' => Self::K,
             'A' => Self::A,
-            c => panic!("{}", c),
-        }
+            c => return Err(ParseError::Str(format!("unexpected card {}", c))),
+        })
+    }
+}
+
+impl Display for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_char())
     }
-    fn to_char(&self) -> char {
+}
+
+impl Card {
+    const ALL: [Self; 13] = [
+        Self::Two,
+        Self::Three,
+        Self::Four,
+        Self::Five,
+        Self::Six,
+        Self::Seven,
+        Self::Eight,
+        Self::Nine,
+        Self::T,
+        Self::J,
+        Self::Q,
+        Self::K,
+        Self::A,
+    ];
+
+    fn to_char(self) -> char {
         match self {
-            Self::Joker => '🂿',
             Self::Two => '2',
             Self::Three => '3',
             Self::Four => '4',
@@ -59,12 +88,6 @@ impl Card {
             Self::A => 'A',
         }
     }
-    fn to_joker(&self) -> Self {
-        match self {
-            Self::J => Self::Joker,
-            _ => self.clone(),
-        }
-    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -77,9 +100,12 @@ impl FromStr for Hand {
         Ok(Self(
             s.chars()
                 .map(Card::from_char)
-                .collect::<Vec<_>>()
+                .collect::<Result<Vec<_>, _>>()?
                 .try_into()
-                .unwrap(),
+                .or(Err(ParseError::Str(format!(
+                    "hand {} should have 5 cards",
+                    s
+                ))))?,
         ))
     }
 }
@@ -89,20 +115,11 @@ impl Display for Hand {
         write!(
             f,
             "{}",
-            self.0.iter().map(Card::to_char).collect::<String>()
-        )
-    }
-}
-
-impl Hand {
-    fn to_joker(&self) -> Self {
-        Self(
             self.0
                 .iter()
-                .map(Card::to_joker)
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap(),
+                .copied()
+                .map(Card::to_char)
+                .collect::<String>()
         )
     }
 }
@@ -114,12 +131,16 @@ impl FromStr for CamelCards {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_line = |line: &str| -> Result<_, ParseError> {
+            let (hand, bid) = line
+                .split_once(' ')
+                .ok_or(ParseError::Str(format!("unexpected {}", line)))?;
+            Ok((hand.parse()?, bid.parse()?))
+        };
         Ok(Self(
             s.lines()
-                .map(|line| -> Result<_, ParseError> {
-                    let (hand, bid) = line.split_once(' ').unwrap();
-                    Ok((hand.parse()?, bid.parse()?))
-                })
+                .enumerate()
+                .map(|(index, line)| parse_line(line).map_err(|e| e.at_line(index + 1)))
                 .collect::<Result<Vec<_>, _>>()?,
         ))
     }
@@ -129,8 +150,8 @@ pub fn parse(input: &str) -> ParseResult<CamelCards> {
     input.parse()
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-enum HandType {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HandType {
     HighCard,
     OnePair,
     TwoPair,
@@ -140,8 +161,23 @@ enum HandType {
     FiveKind,
 }
 
+impl Display for HandType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::HighCard => "High card",
+            Self::OnePair => "One pair",
+            Self::TwoPair => "Two pair",
+            Self::ThreeKind => "Three of a kind",
+            Self::FullHouse => "Full house",
+            Self::FourKind => "Four of a kind",
+            Self::FiveKind => "Five of a kind",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 impl HandType {
-    fn _from_hand_sort(hand: &Hand) -> Self {
+    fn from_hand_sort(hand: &Hand) -> Self {
         let mut cards = hand.0.to_owned();
         cards.sort_unstable();
         cards.reverse();
@@ -162,12 +198,8 @@ impl HandType {
     fn from_hand_counter(hand: &Hand) -> Self {
         let mut counter = HashMap::<Card, usize>::new();
         for card in &hand.0 {
-            counter
-                .entry(card.clone())
-                .and_modify(|e| *e += 1)
-                .or_insert(1);
+            counter.entry(*card).and_modify(|e| *e += 1).or_insert(1);
         }
-        let count_joker = counter.remove(&Card::Joker).unwrap_or(0);
         let mut shape: Vec<(Card, usize)> = counter.into_iter().collect();
         if shape.len() <= 1 {
             return Self::FiveKind;
@@ -175,7 +207,7 @@ impl HandType {
         shape.select_nth_unstable_by(1, |(_a_card, a_count), (_b_card, b_count)| {
             b_count.cmp(a_count)
         });
-        let top_cards = [shape[0].1 + count_joker, shape[1].1];
+        let top_cards = [shape[0].1, shape[1].1];
         match top_cards {
             [4, _] => Self::FourKind,
             [3, 2] => Self::FullHouse,
@@ -187,60 +219,286 @@ impl HandType {
     }
 }
 
-pub fn part1(cards: &CamelCards) -> PartOutput<usize> {
-    let mut hands: Vec<(HandType, Hand, usize)> = cards
+/// How the type of a hand without wildcards is determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Classifier {
+    Counter,
+    Sort,
+}
+
+impl FromStr for Classifier {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "counter" => Ok(Self::Counter),
+            "sort" => Ok(Self::Sort),
+            _ => Err(ParseError::Str(format!("unknown classifier {}", s))),
+        }
+    }
+}
+
+impl Classifier {
+    fn classify(self, hand: &Hand) -> HandType {
+        match self {
+            Self::Counter => HandType::from_hand_counter(hand),
+            Self::Sort => HandType::from_hand_sort(hand),
+        }
+    }
+}
+
+/// Hands are ordered by type first, then by the strength of each card in turn.
+type SortKey = (HandType, [usize; 5]);
+
+/// The rules of a game of Camel Cards.
+///
+/// A rule set can be written as a small config, one `key: value` per line.
+/// Missing keys keep the value of the standard rules:
+///
+/// ```text
+/// ranking: J23456789TQKA
+/// wildcards: J
+/// classifier: counter
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// Every card, from weakest to strongest.
+    ranking: [Card; 13],
+    /// Cards that act like whichever card makes the strongest hand type.
+    wildcards: Vec<Card>,
+    classifier: Classifier,
+}
+
+impl Rules {
+    pub fn standard() -> Self {
+        Self {
+            ranking: Card::ALL,
+            wildcards: Vec::new(),
+            classifier: Classifier::Counter,
+        }
+    }
+
+    pub fn jokers() -> Self {
+        let mut ranking = Card::ALL;
+        ranking[..=9].rotate_right(1);
+        Self {
+            ranking,
+            wildcards: vec![Card::J],
+            classifier: Classifier::Counter,
+        }
+    }
+
+    pub fn with_classifier(self, classifier: Classifier) -> Self {
+        Self { classifier, ..self }
+    }
+
+    fn strength(&self, card: Card) -> usize {
+        self.ranking.iter().position(|&c| c == card).unwrap()
+    }
+
+    fn is_wild(&self, card: Card) -> bool {
+        self.wildcards.contains(&card)
+    }
+
+    /// The hand type of a hand, with every wildcard in it substituted by the
+    /// card that makes the strongest hand type.
+    pub fn hand_type(&self, hand: &Hand) -> HandType {
+        self.best_substitution(hand).0
+    }
+
+    /// The strongest hand type reachable by substituting the wildcards in a
+    /// hand, and the card substituted for them, if any.
+    fn best_substitution(&self, hand: &Hand) -> (HandType, Option<Card>) {
+        if !hand.0.iter().any(|&card| self.is_wild(card)) {
+            return (self.classifier.classify(hand), None);
+        }
+        let mut candidates: Vec<Card> = hand
+            .0
+            .iter()
+            .copied()
+            .filter(|&card| !self.is_wild(card))
+            .collect();
+        // A hand of only wildcards becomes five of a kind of any card.
+        if candidates.is_empty() {
+            candidates.push(hand.0[0]);
+        }
+        candidates
+            .into_iter()
+            .map(|substitute| {
+                (
+                    self.classifier.classify(&self.substitute(hand, substitute)),
+                    Some(substitute),
+                )
+            })
+            .max_by_key(|(hand_type, _substitute)| *hand_type)
+            .unwrap()
+    }
+
+    fn substitute(&self, hand: &Hand, substitute: Card) -> Hand {
+        Hand(
+            hand.0
+                .map(|card| if self.is_wild(card) { substitute } else { card }),
+        )
+    }
+
+    fn sort_key(&self, hand: &Hand) -> SortKey {
+        (self.hand_type(hand), hand.0.map(|card| self.strength(card)))
+    }
+}
+
+impl FromStr for Rules {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = Self::standard();
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or(ParseError::Str(format!("unexpected {}", line)))?;
+            let cards = || {
+                value
+                    .trim()
+                    .chars()
+                    .map(Card::from_char)
+                    .collect::<Result<Vec<_>, _>>()
+            };
+            match key.trim() {
+                "ranking" => {
+                    let ranking = cards()?;
+                    let invalid = ParseError::Str(format!(
+                        "ranking {} should contain every card once",
+                        value.trim()
+                    ));
+                    if !Card::ALL.iter().all(|card| ranking.contains(card)) {
+                        return Err(invalid);
+                    }
+                    rules.ranking = ranking.try_into().or(Err(invalid))?;
+                },
+                "wildcards" => rules.wildcards = cards()?,
+                "classifier" => rules.classifier = value.trim().parse()?,
+                key => return Err(ParseError::Str(format!("unknown rule {}", key))),
+            }
+        }
+        Ok(rules)
+    }
+}
+
+#[derive(Debug)]
+pub enum RankError {
+    Tie(Hand, Hand),
+}
+
+impl Display for RankError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tie(a, b) => write!(f, "hands {} and {} are tied", a, b),
+        }
+    }
+}
+
+impl std::error::Error for RankError {}
+
+impl From<RankError> for SolveError {
+    fn from(value: RankError) -> Self {
+        Self::Str(value.to_string())
+    }
+}
+
+/// The indices of the hands sorted from weakest to strongest, so that each
+/// hand's rank is its position plus one.
+fn rank(cards: &CamelCards, rules: &Rules) -> Result<Vec<usize>, RankError> {
+    let mut hands: Vec<(SortKey, usize)> = cards
         .0
         .iter()
-        .map(|(hand, bid)| (HandType::from_hand_counter(hand), hand.clone(), *bid))
+        .enumerate()
+        .map(|(index, (hand, _bid))| (rules.sort_key(hand), index))
         .collect();
-    hands.sort_by(
-        |(a_hand_type, a_hand, _a_bid), (b_hand_type, b_hand, _b_bid)| {
-            a_hand_type
-                .cmp(b_hand_type)
-                .then(a_hand.cmp(b_hand))
-                .then_with(|| panic!())
-        },
-    );
+    hands.sort_unstable();
     log::debug!("hands: {:?}", hands);
-    PartOutput {
-        answer: hands
-            .into_iter()
-            .map(|(_hand_type, _hand, bid)| bid)
-            .enumerate()
-            .map(|(idx, bid)| (idx + 1) * bid)
-            .sum(),
+    if let Some(tie) = hands.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(RankError::Tie(
+            cards.0[tie[0].1].0.clone(),
+            cards.0[tie[1].1].0.clone(),
+        ));
     }
+    Ok(hands.into_iter().map(|(_key, index)| index).collect())
 }
 
-pub fn part2(cards: &CamelCards) -> PartOutput<usize> {
-    let mut hands: Vec<(HandType, Hand, usize)> = cards
+pub fn total_winnings(cards: &CamelCards, rules: &Rules) -> Result<usize, RankError> {
+    Ok(rank(cards, rules)?
+        .into_iter()
+        .map(|index| cards.0[index].1)
+        .enumerate()
+        .map(|(idx, bid)| (idx + 1) * bid)
+        .sum())
+}
+
+/// How a single hand was scored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandReport {
+    pub cards: [Card; 5],
+    /// The type of the hand as dealt, ignoring wildcards.
+    pub hand_type: HandType,
+    /// The card substituted for the wildcards in the hand, if any.
+    pub substitute: Option<Card>,
+    /// The hand after substituting the wildcards.
+    pub effective_cards: [Card; 5],
+    pub effective_type: HandType,
+    pub bid: usize,
+    pub rank: usize,
+    pub winnings: usize,
+}
+
+/// Explain the score of every hand, in the order they were dealt.
+pub fn explain(cards: &CamelCards, rules: &Rules) -> Result<Vec<HandReport>, RankError> {
+    let mut ranks = vec![0; cards.0.len()];
+    for (idx, index) in rank(cards, rules)?.into_iter().enumerate() {
+        ranks[index] = idx + 1;
+    }
+    Ok(cards
         .0
         .iter()
-        .map(|(hand, bid)| {
-            (
-                HandType::from_hand_counter(&hand.to_joker()),
-                hand.to_joker(),
-                *bid,
-            )
+        .zip(ranks)
+        .map(|((hand, bid), rank)| {
+            let (effective_type, substitute) = rules.best_substitution(hand);
+            HandReport {
+                cards: hand.0,
+                hand_type: rules.classifier.classify(hand),
+                substitute,
+                effective_cards: substitute.map_or(hand.0, |card| rules.substitute(hand, card).0),
+                effective_type,
+                bid: *bid,
+                rank,
+                winnings: rank * bid,
+            }
         })
-        .collect();
-    hands.sort_by(
-        |(a_hand_type, a_hand, _a_bid), (b_hand_type, b_hand, _b_bid)| {
-            a_hand_type
-                .cmp(b_hand_type)
-                .then(a_hand.cmp(b_hand))
-                .then_with(|| panic!())
-        },
-    );
-    log::debug!("hands: {:?}", hands);
-    PartOutput {
-        answer: hands
-            .into_iter()
-            .map(|(_hand_type, _hand, bid)| bid)
-            .enumerate()
-            .map(|(idx, bid)| (idx + 1) * bid)
-            .sum(),
-    }
+        .collect())
+}
+
+pub fn part1(cards: &CamelCards, _params: &Params, _context: &Context) -> PartResult<usize> {
+    Ok(PartOutput {
+        answer: total_winnings(cards, &Rules::standard())?,
+    })
+}
+
+pub fn part2(cards: &CamelCards, _params: &Params, _context: &Context) -> PartResult<usize> {
+    Ok(PartOutput {
+        answer: total_winnings(cards, &Rules::jokers())?,
+    })
+}
+
+pub fn part1_sort(cards: &CamelCards, _params: &Params, _context: &Context) -> PartResult<usize> {
+    let rules = Rules::standard().with_classifier(Classifier::Sort);
+    Ok(PartOutput {
+        answer: total_winnings(cards, &rules)?,
+    })
+}
+
+pub fn part2_sort(cards: &CamelCards, _params: &Params, _context: &Context) -> PartResult<usize> {
+    let rules = Rules::jokers().with_classifier(Classifier::Sort);
+    Ok(PartOutput {
+        answer: total_winnings(cards, &rules)?,
+    })
 }
 
 pub const DAY: Day<CamelCards, usize, 1, 0, 0> = Day {
@@ -249,12 +507,20 @@ pub const DAY: Day<CamelCards, usize, 1, 0, 0> = Day {
         "The total winnings are {answer}.",
         "The new total winnings with jokers are {answer}.",
     ),
+    params: &[],
     calc: DayCalc {
         parse,
         part1,
         part2,
+        alternatives: &[Implementation {
+            name: "sort",
+            part1: Some(part1_sort),
+            part2: Some(part2_sort),
+        }],
     },
-    examples: Examples::single(include_str!("../../examples/day07.txt")),
+    examples: Examples::single(
+        Example::new(include_str!("../../examples/day07.txt")).answers("6440", "5905"),
+    ),
 };
 
 #[cfg(test)]
@@ -262,6 +528,41 @@ mod tests {
     use test_log::test;
 
     use super::*;
+    use crate::test::assert_implementations_agree;
+    use crate::Part;
+
+    /// Deal distinct random hands, so that no two hands can tie.
+    fn random_input(seed: u64, count: usize) -> String {
+        let mut state = seed;
+        let mut next = move || {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let mut hands = std::collections::HashSet::new();
+        let mut input = String::new();
+        while hands.len() < count {
+            let hand: String = (0..5)
+                .map(|_| Card::ALL[(next() % 13) as usize].to_char())
+                .collect();
+            if hands.insert(hand.clone()) {
+                input.push_str(&format!("{} {}\n", hand, next() % 1000 + 1));
+            }
+        }
+        input
+    }
+
+    #[test]
+    fn test_implementations_random() {
+        let params = Params::new(DAY.params);
+        for seed in 1..=20 {
+            let input = random_input(seed, 200);
+            assert_implementations_agree(&DAY, Part::First, &input, &params);
+            assert_implementations_agree(&DAY, Part::Second, &input, &params);
+        }
+    }
 
     #[test]
     fn test_card() {
@@ -337,4 +638,64 @@ mod tests {
             HandType::HighCard
         );
     }
+
+    #[test]
+    fn test_rules() {
+        assert_eq!("".parse::<Rules>().unwrap(), Rules::standard());
+        assert_eq!(
+            "ranking: J23456789TQKA\nwildcards: J\nclassifier: counter"
+                .parse::<Rules>()
+                .unwrap(),
+            Rules::jokers()
+        );
+        assert!("ranking: 23456789TQKA".parse::<Rules>().is_err());
+        assert!("ranking: 223456789TQKA".parse::<Rules>().is_err());
+        assert!("wildcards: X".parse::<Rules>().is_err());
+    }
+
+    #[test]
+    fn test_wildcards() {
+        let rules = Rules::jokers();
+        assert_eq!(
+            rules.hand_type(&"QJJQ2".parse().unwrap()),
+            HandType::FourKind
+        );
+        assert_eq!(
+            rules.hand_type(&"JJJJJ".parse().unwrap()),
+            HandType::FiveKind
+        );
+        assert!(
+            rules.sort_key(&"JKKK2".parse().unwrap()) < rules.sort_key(&"QQQQ2".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let cards = parse(DAY.examples.common[0].input).unwrap();
+        let report = explain(&cards, &Rules::jokers()).unwrap();
+        assert_eq!(report.len(), 5);
+        assert_eq!(
+            report[1],
+            HandReport {
+                cards: "T55J5".parse::<Hand>().unwrap().0,
+                hand_type: HandType::ThreeKind,
+                substitute: Some(Card::Five),
+                effective_cards: "T5555".parse::<Hand>().unwrap().0,
+                effective_type: HandType::FourKind,
+                bid: 684,
+                rank: 3,
+                winnings: 2052,
+            }
+        );
+        assert_eq!(report.iter().map(|hand| hand.winnings).sum::<usize>(), 5905);
+    }
+
+    #[test]
+    fn test_tie() {
+        let cards = parse("32T3K 765\n32T3K 684").unwrap();
+        assert!(matches!(
+            total_winnings(&cards, &Rules::standard()),
+            Err(RankError::Tie(_, _))
+        ));
+    }
 }
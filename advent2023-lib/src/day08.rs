@@ -1,10 +1,15 @@
 use std::collections::HashMap;
+use std::fmt::Display;
 use std::str::FromStr;
 
 use once_cell::sync::Lazy;
 use regex::Regex;
 
-use crate::{Day, DayCalc, Examples, ParseError, ParseResult, PartOutput};
+use crate::parser::FromChar;
+use crate::{
+    Context, Day, DayCalc, Example, Examples, Param, ParamKind, Params, ParseError, ParseResult,
+    Part, PartOutput, PartResult, SolveError,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub enum Direction {
@@ -12,24 +17,34 @@ pub enum Direction {
     Right,
 }
 
-impl Direction {
-    fn from_char(c: char) -> Self {
+impl FromChar for Direction {
+    type Err = ParseError;
+
+    fn from_char(c: char) -> Result<Self, Self::Err> {
         match c {
-            'L' => Self::Left,
-            'R' => Self::Right,
-            _ => panic!(),
+            'L' => Ok(Self::Left),
+            'R' => Ok(Self::Right),
+            c => Err(ParseError::Str(format!("unexpected direction {}", c))),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Node([char; 3]);
 
 impl FromStr for Node {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(s.chars().collect::<Vec<_>>().try_into().unwrap()))
+        Ok(Self(s.chars().collect::<Vec<_>>().try_into().or(Err(
+            ParseError::Str(format!("node {} should be 3 characters", s)),
+        ))?))
+    }
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.iter().collect::<String>())
     }
 }
 
@@ -48,15 +63,23 @@ impl Node {
     }
 }
 
+/// The network with node names interned into dense indices.
+///
+/// `edges[i]` holds the left and right neighbours of the node named `names[i]`.
 #[derive(Debug)]
 pub struct Documents {
     instructions: Vec<Direction>,
-    nodes: HashMap<Node, [Node; 2]>,
+    names: Vec<Node>,
+    indices: HashMap<Node, u32>,
+    edges: Vec<[u32; 2]>,
 }
 
 static RE_NODE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"(?<from>\w{3}) = \((?<left>\w{3}), (?<right>\w{3})\)").unwrap());
+    Lazy::new(|| Regex::new(r"^(?<from>\w{3}) = \((?<left>\w{3}), (?<right>\w{3})\)$").unwrap());
 
+/// Parsing checks the form of the network but not that it has AAA and ZZZ:
+/// the part 2 example has neither, and the nodes to walk between are
+/// parameters, so a missing node is reported when solving.
 impl FromStr for Documents {
     type Err = ParseError;
 
@@ -64,32 +87,99 @@ impl FromStr for Documents {
         let mut lines = s.lines();
         let instructions = lines
             .next()
-            .unwrap()
+            .ok_or(ParseError::Empty)?
             .chars()
             .map(Direction::from_char)
-            .collect();
-        assert_eq!(lines.next(), Some(""));
-        let nodes = lines
-            .map(|s| {
-                let Some(captures) = RE_NODE.captures(s) else {
-                    return Err(ParseError::Str(format!("unexpected {}", s)));
+            .collect::<Result<Vec<_>, ParseError>>()
+            .map_err(|e| e.at_line(1))?;
+        if instructions.is_empty() {
+            return Err(ParseError::Str(String::from("no instructions")).at_line(1));
+        }
+        if lines.next() != Some("") {
+            return Err(ParseError::Str(String::from(
+                "instructions should be followed by a blank line",
+            ))
+            .at_line(2));
+        }
+        let mut names = Vec::new();
+        let mut indices = HashMap::new();
+        let mut next_names = Vec::new();
+        // The nodes start on the third line.
+        for (index, line) in lines.enumerate() {
+            let at_line = |e: ParseError| e.at_line(index + 3);
+            let Some(captures) = RE_NODE.captures(line) else {
+                return Err(at_line(ParseError::Str(format!("unexpected {}", line))));
+            };
+            let from = Node::from_str(&captures["from"]).map_err(at_line)?;
+            if indices
+                .insert(from, u32::try_from(names.len()).unwrap())
+                .is_some()
+            {
+                return Err(at_line(ParseError::Str(format!(
+                    "node {} is defined twice",
+                    from
+                ))));
+            }
+            names.push(from);
+            next_names.push([
+                Node::from_str(&captures["left"]).map_err(at_line)?,
+                Node::from_str(&captures["right"]).map_err(at_line)?,
+            ]);
+        }
+        let edges = names
+            .iter()
+            .zip(next_names)
+            .enumerate()
+            .map(|(index, (from, [left, right]))| {
+                let lookup = |to: Node| {
+                    indices.get(&to).copied().ok_or(
+                        ParseError::Str(format!("node {} leads to undefined node {}", from, to))
+                            .at_line(index + 3),
+                    )
                 };
-                Ok((
-                    Node::from_str(&captures["from"])?,
-                    [
-                        Node::from_str(&captures["left"])?,
-                        Node::from_str(&captures["right"])?,
-                    ],
-                ))
+                Ok([lookup(left)?, lookup(right)?])
             })
-            .collect::<Result<HashMap<_, _>, _>>()?;
+            .collect::<Result<Vec<_>, ParseError>>()?;
         Ok(Self {
             instructions,
-            nodes,
+            names,
+            indices,
+            edges,
         })
     }
 }
 
+#[derive(Debug)]
+pub enum RouteError {
+    InvalidNode(String),
+    MissingNode(Node),
+    NoStart,
+    Overflow,
+    Unreachable { start: Node, steps: usize },
+}
+
+impl Display for RouteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidNode(name) => write!(f, "{} is not a node, names are 3 characters", name),
+            Self::MissingNode(node) => write!(f, "node {} is not in the network", node),
+            Self::NoStart => write!(f, "there are no starting nodes in the network"),
+            Self::Overflow => write!(f, "the ghosts take too many steps to count"),
+            Self::Unreachable { start, steps } => write!(
+                f,
+                "no end can be reached from {}, the route loops after {} steps",
+                start, steps
+            ),
+        }
+    }
+}
+
+impl From<RouteError> for SolveError {
+    fn from(value: RouteError) -> Self {
+        Self::Str(value.to_string())
+    }
+}
+
 impl Documents {
     fn get_direction(&self, index: usize) -> Direction {
         *self
@@ -97,24 +187,113 @@ impl Documents {
             .get(index % self.instructions.len())
             .unwrap()
     }
+
+    fn index(&self, node: &Node) -> Result<u32, RouteError> {
+        self.indices
+            .get(node)
+            .copied()
+            .ok_or(RouteError::MissingNode(*node))
+    }
+
+    fn name(&self, index: u32) -> &Node {
+        &self.names[index as usize]
+    }
+
+    /// Follow the instructions from `start` until `is_end` is satisfied.
+    ///
+    /// The walk is deterministic in the state (node, instruction index), so
+    /// arriving at the same node at the start of the instructions twice
+    /// means the route loops without ever reaching an end.
+    fn walk(&self, start: u32, is_end: impl Fn(u32) -> bool) -> Result<usize, RouteError> {
+        let mut seen = vec![false; self.names.len()];
+        let mut step = 0;
+        let mut node = start;
+        while !is_end(node) {
+            if step % self.instructions.len() == 0 {
+                if seen[node as usize] {
+                    return Err(RouteError::Unreachable {
+                        start: *self.name(start),
+                        steps: step,
+                    });
+                }
+                seen[node as usize] = true;
+            }
+            let next = self.edges[node as usize];
+            match self.get_direction(step) {
+                Direction::Left => node = next[0],
+                Direction::Right => node = next[1],
+            }
+            step += 1;
+        }
+        Ok(step)
+    }
+
+    /// Render the network as a Graphviz DOT graph.
+    ///
+    /// Starting nodes are filled green and end nodes red, with `AAA` and
+    /// `ZZZ` drawn as double circles. Names are quoted, as a DOT ID cannot
+    /// start with a digit.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph network {\n");
+        for name in &self.names {
+            let mut attributes = Vec::new();
+            if *name == Node::start() || *name == Node::end() {
+                attributes.push("shape=doublecircle");
+            }
+            if name.ghost_start() {
+                attributes.push("style=filled, fillcolor=palegreen");
+            } else if name.ghost_end() {
+                attributes.push("style=filled, fillcolor=salmon");
+            }
+            if !attributes.is_empty() {
+                dot.push_str(&format!("    \"{}\" [{}];\n", name, attributes.join(", ")));
+            }
+        }
+        for (from, [left, right]) in self.names.iter().zip(&self.edges) {
+            if left == right {
+                dot.push_str(&format!(
+                    "    \"{}\" -> \"{}\" [label=\"LR\"];\n",
+                    from,
+                    self.name(*left)
+                ));
+            } else {
+                dot.push_str(&format!(
+                    "    \"{}\" -> \"{}\" [label=\"L\"];\n",
+                    from,
+                    self.name(*left)
+                ));
+                dot.push_str(&format!(
+                    "    \"{}\" -> \"{}\" [label=\"R\"];\n",
+                    from,
+                    self.name(*right)
+                ));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
 }
 
 pub fn parse(input: &str) -> ParseResult<Documents> {
     input.parse()
 }
 
-pub fn part1(maps: &Documents) -> PartOutput<usize> {
-    let mut step = 0;
-    let mut node = Node::start();
-    while node != Node::end() {
-        let next = maps.nodes.get(&node).unwrap();
-        match maps.get_direction(step) {
-            Direction::Left => node = next[0].clone(),
-            Direction::Right => node = next[1].clone(),
-        }
-        step += 1;
-    }
-    PartOutput { answer: step }
+fn node_param(params: &Params, name: &str) -> Result<Node, RouteError> {
+    params
+        .parse(name)
+        .map_err(|_| RouteError::InvalidNode(params.get(name).to_owned()))
+}
+
+fn steps_to_end(maps: &Documents, start: &Node, end: &Node) -> Result<usize, RouteError> {
+    let start = maps.index(start)?;
+    let end = maps.index(end)?;
+    maps.walk(start, |node| node == end)
+}
+
+pub fn part1(maps: &Documents, params: &Params, _context: &Context) -> PartResult<usize> {
+    let steps = node_param(params, "start")
+        .and_then(|start| steps_to_end(maps, &start, &node_param(params, "end")?));
+    Ok(PartOutput { answer: steps? })
 }
 
 fn greatest_common_divisor(a: usize, b: usize) -> usize {
@@ -125,59 +304,145 @@ fn greatest_common_divisor(a: usize, b: usize) -> usize {
     }
 }
 
-fn least_common_multiple(a: usize, b: usize) -> usize {
-    a.checked_mul(b)
-        .unwrap()
-        .checked_div(greatest_common_divisor(a, b))
-        .unwrap()
+fn least_common_multiple(a: usize, b: usize) -> Option<usize> {
+    (a / greatest_common_divisor(a, b)).checked_mul(b)
 }
 
-pub fn part2(maps: &Documents) -> PartOutput<usize> {
-    let starting_nodes: Vec<Node> = maps
-        .nodes
-        .keys()
-        .filter(|n| n.ghost_start())
-        .cloned()
+fn ghost_steps_to_end(maps: &Documents) -> Result<usize, RouteError> {
+    let starting_nodes: Vec<u32> = (0..)
+        .zip(&maps.names)
+        .filter(|(_index, name)| name.ghost_start())
+        .map(|(index, _name)| index)
         .collect();
     log::debug!("starting: {:?}", starting_nodes);
-    let steps: Vec<usize> = starting_nodes
+    let steps = starting_nodes
         .into_iter()
-        .map(|mut node| {
-            let mut step = 0;
-            while !node.ghost_end() {
-                let next = maps.nodes.get(&node).unwrap();
-                match maps.get_direction(step) {
-                    Direction::Left => node = next[0].clone(),
-                    Direction::Right => node = next[1].clone(),
-                }
-                step += 1;
-            }
-            step
-        })
-        .collect();
+        .map(|node| maps.walk(node, |node| maps.name(node).ghost_end()))
+        .collect::<Result<Vec<usize>, _>>()?;
     log::debug!("steps: {:?}", steps);
-    PartOutput {
-        answer: steps.into_iter().reduce(least_common_multiple).unwrap(),
-    }
+    let mut steps = steps.into_iter();
+    let first = steps.next().ok_or(RouteError::NoStart)?;
+    steps.try_fold(first, |total, steps| {
+        least_common_multiple(total, steps).ok_or(RouteError::Overflow)
+    })
+}
+
+pub fn part2(maps: &Documents, _params: &Params, _context: &Context) -> PartResult<usize> {
+    Ok(PartOutput {
+        answer: ghost_steps_to_end(maps)?,
+    })
 }
 
 pub const DAY: Day<Documents, usize, 0, 2, 1> = Day {
     title: "Haunted Wasteland",
     display: (
-        "{answer} steps are required to reach ZZZ.",
+        "{answer} steps are required to reach the end.",
         "{answer} steps are required to reach **Z.",
     ),
+    params: &[
+        Param {
+            name: "start",
+            description: "The node to start from",
+            kind: ParamKind::Text,
+            default: "AAA",
+        },
+        Param {
+            name: "end",
+            description: "The node to reach",
+            kind: ParamKind::Text,
+            default: "ZZZ",
+        },
+    ],
     calc: DayCalc {
         parse,
         part1,
         part2,
+        alternatives: &[],
     },
     examples: Examples {
         common: [],
         part1: [
-            include_str!("../../examples/day08-1-2.txt"),
-            include_str!("../../examples/day08-1-1.txt"),
+            Example::new(include_str!("../../examples/day08-1-2.txt")).answer(Part::First, "6"),
+            Example::new(include_str!("../../examples/day08-1-1.txt")).answer(Part::First, "2"),
         ],
-        part2: [include_str!("../../examples/day08-2.txt")],
+        part2: [Example::new(include_str!("../../examples/day08-2.txt")).answer(Part::Second, "6")],
     },
 };
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_undefined_node() {
+        let e = parse("LR\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)").unwrap_err();
+        assert_eq!(e.line(), Some(3));
+    }
+
+    #[test]
+    fn test_trailing_text() {
+        for input in [
+            "LR\n\nXAAAA = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)",
+            "LR\n\nAAA = (ZZZ, ZZZ)junk\nZZZ = (ZZZ, ZZZ)",
+        ] {
+            assert_eq!(parse(input).unwrap_err().line(), Some(3));
+        }
+    }
+
+    #[test]
+    fn test_missing_end() {
+        let maps = parse("LR\n\nAAA = (AAA, AAA)").unwrap();
+        assert!(matches!(
+            steps_to_end(&maps, &Node::start(), &Node::end()),
+            Err(RouteError::MissingNode(node)) if node == Node::end()
+        ));
+    }
+
+    #[test]
+    fn test_dot() {
+        let maps = parse(DAY.examples.part1[0].input).unwrap();
+        assert_eq!(
+            maps.to_dot(),
+            "digraph network {
+    \"AAA\" [shape=doublecircle, style=filled, fillcolor=palegreen];
+    \"ZZZ\" [shape=doublecircle, style=filled, fillcolor=salmon];
+    \"AAA\" -> \"BBB\" [label=\"LR\"];
+    \"BBB\" -> \"AAA\" [label=\"L\"];
+    \"BBB\" -> \"ZZZ\" [label=\"R\"];
+    \"ZZZ\" -> \"ZZZ\" [label=\"LR\"];
+}
+"
+        );
+        let ghosts = parse(DAY.examples.part2[0].input).unwrap().to_dot();
+        assert!(ghosts.contains("    \"11A\" [style=filled, fillcolor=palegreen];\n"));
+        assert!(ghosts.contains("    \"22C\" -> \"22Z\" [label=\"LR\"];\n"));
+    }
+
+    #[test]
+    fn test_unreachable_end() {
+        let maps = parse("LR\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)").unwrap();
+        assert!(matches!(
+            steps_to_end(&maps, &Node::start(), &Node::end()),
+            Err(RouteError::Unreachable { steps: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_route_params() {
+        let maps = parse(DAY.examples.part1[1].input).unwrap();
+        let mut params = Params::new(DAY.params);
+        params.set("start", "CCC").unwrap();
+        params.set("end", "GGG").unwrap();
+        assert_eq!(
+            part1(&maps, &params, &Context::default()).unwrap().answer,
+            1
+        );
+        params.set("end", "GGGG").unwrap();
+        assert!(matches!(
+            node_param(&params, "end"),
+            Err(RouteError::InvalidNode(name)) if name == "GGGG"
+        ));
+    }
+}
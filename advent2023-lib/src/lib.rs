@@ -1,25 +1,30 @@
+use std::any::Any;
 use std::char::ParseCharError;
 use std::collections::btree_map::BTreeMap;
 use std::fmt::Display;
 use std::fs;
 use std::num::ParseIntError;
-use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use recap::Error as RecapError;
 use strum::ParseError as StrumParseError;
+use web_time::{Duration, Instant};
 
-mod day01;
-mod day02;
-mod day03;
-mod day04;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
 mod day05;
 mod day06;
-mod day07;
-mod day08;
+pub mod day07;
+pub mod day08;
 pub mod parser;
+pub mod scanner;
 mod test;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Part {
     First,
     Second,
@@ -33,6 +38,46 @@ pub enum ParseError {
     Str(String),
     Strum(StrumParseError),
     Recap(RecapError),
+    /// The error is on a line of the input, numbered from 1.
+    Line {
+        line: usize,
+        error: Box<ParseError>,
+    },
+}
+
+impl ParseError {
+    /// Place the error on a line of the input, numbered from 1, unless it is
+    /// already on one.
+    pub fn at_line(self, line: usize) -> Self {
+        match self {
+            Self::Line { .. } => self,
+            error => Self::Line {
+                line,
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// The line of the input the error is on, numbered from 1, if it is known.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Self::Line { line, .. } => Some(*line),
+            _ => None,
+        }
+    }
+
+    /// The name of the kind of error, ignoring the line it is on.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Empty => "Empty",
+            Self::Int(_) => "Int",
+            Self::Char(_) => "Char",
+            Self::Str(_) => "Str",
+            Self::Strum(_) => "Strum",
+            Self::Recap(_) => "Recap",
+            Self::Line { error, .. } => error.kind(),
+        }
+    }
 }
 
 impl From<ParseIntError> for ParseError {
@@ -61,39 +106,371 @@ impl From<RecapError> for ParseError {
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "invalid input for day")
+        match self {
+            Self::Empty => write!(f, "the input is empty"),
+            Self::Int(e) => write!(f, "invalid number, {}", e),
+            Self::Char(e) => write!(f, "invalid character, {}", e),
+            Self::Str(reason) => write!(f, "{}", reason),
+            Self::Strum(e) => write!(f, "invalid name, {}", e),
+            Self::Recap(e) => write!(f, "{}", e),
+            Self::Line { line, error } => write!(f, "line {}: {}", line, error),
+        }
     }
 }
 
+impl std::error::Error for ParseError {}
+
 pub type ParseResult<D> = std::result::Result<D, ParseError>;
 
+/// Why a part has no answer for input that was parsed.
+#[derive(Debug)]
+pub enum SolveError {
+    /// There is nothing to take the answer from, such as the minimum of no
+    /// values.
+    Empty,
+    /// The part was cancelled or ran past its deadline before finishing.
+    Cancelled,
+    Str(String),
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "there is nothing to find an answer in"),
+            Self::Cancelled => write!(f, "timed out"),
+            Self::Str(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct PartOutput<O> {
     pub answer: O,
 }
 
-pub struct DayCalc<D, O> {
+pub type PartResult<O> = std::result::Result<PartOutput<O>, SolveError>;
+
+/// Either the input could not be parsed, or a part could not be solved.
+#[derive(Debug)]
+pub enum DayError {
+    Parse(ParseError),
+    Solve(SolveError),
+}
+
+impl From<ParseError> for DayError {
+    fn from(value: ParseError) -> Self {
+        Self::Parse(value)
+    }
+}
+
+impl From<SolveError> for DayError {
+    fn from(value: SolveError) -> Self {
+        Self::Solve(value)
+    }
+}
+
+impl Display for DayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::Solve(SolveError::Cancelled) => write!(f, "timed out"),
+            Self::Solve(e) => write!(f, "no answer: {}", e),
+        }
+    }
+}
+
+impl DayError {
+    /// Whether the part was stopped, rather than failing.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Solve(SolveError::Cancelled))
+    }
+}
+
+pub type CalcResult<T> = std::result::Result<T, DayError>;
+
+/// Lets the caller of a part stop it, by cancelling it or by giving it a
+/// deadline. Parts check it at the boundaries of their long loops.
+///
+/// Clones share the cancellation, so a clone can cancel a part running on
+/// another thread.
+#[derive(Clone, Default)]
+pub struct Context {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    progress: Option<Arc<dyn Fn(Progress) + Send + Sync>>,
+}
+
+impl std::fmt::Debug for Context {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Context")
+            .field("cancelled", &self.cancelled)
+            .field("deadline", &self.deadline)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+/// How much of its work a part has done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub done: u64,
+    /// The total amount of work, if the part knows it.
+    pub total: Option<u64>,
+}
+
+impl Context {
+    /// A context that stops parts running for longer than `timeout`.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            deadline: Some(Instant::now() + timeout),
+            ..Default::default()
+        }
+    }
+
+    /// The context with `sink` receiving the progress the part reports.
+    pub fn with_progress(self, sink: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        Self {
+            progress: Some(Arc::new(sink)),
+            ..self
+        }
+    }
+
+    /// Report that `done` out of `total` units of work are done.
+    pub fn report(&self, done: u64, total: Option<u64>) {
+        if let Some(sink) = &self.progress {
+            sink(Progress { done, total });
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Fail with `SolveError::Cancelled` if the part should stop.
+    pub fn check(&self) -> std::result::Result<(), SolveError> {
+        if self.is_cancelled() {
+            Err(SolveError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// How the value of a parameter is checked, and which input is shown for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    /// A non-negative integer.
+    Integer,
+    /// Any text, checked by the day when it is used.
+    Text,
+}
+
+/// A constant of a puzzle that can be changed when solving it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Param {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub kind: ParamKind,
+    pub default: &'static str,
+}
+
+/// The values given to the parameters of a day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Params {
+    specs: &'static [Param],
+    values: BTreeMap<&'static str, String>,
+}
+
+impl Params {
+    /// Every parameter at its default value.
+    pub fn new(specs: &'static [Param]) -> Self {
+        Self {
+            specs,
+            values: BTreeMap::new(),
+        }
+    }
+
+    pub fn specs(&self) -> &'static [Param] {
+        self.specs
+    }
+
+    /// The parameters that have been set, with their values.
+    pub fn values(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        self.values
+            .iter()
+            .map(|(&name, value)| (name, value.as_str()))
+    }
+
+    fn spec(&self, name: &str) -> Option<&'static Param> {
+        self.specs.iter().find(|spec| spec.name == name)
+    }
+
+    /// Set a parameter, checking that it exists and that the value has the
+    /// right kind.
+    pub fn set(&mut self, name: &str, value: &str) -> ParseResult<()> {
+        let Some(spec) = self.spec(name) else {
+            return Err(ParseError::Str(format!(
+                "unknown parameter {}, choose from: {}",
+                name,
+                self.specs
+                    .iter()
+                    .map(|spec| spec.name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        };
+        if spec.kind == ParamKind::Integer {
+            value.parse::<usize>()?;
+        }
+        self.values.insert(spec.name, value.to_owned());
+        Ok(())
+    }
+
+    /// The value of a parameter, or its default if it has not been set.
+    ///
+    /// Panics if the day has no parameter with this name.
+    pub fn get(&self, name: &str) -> &str {
+        match self.values.get(name) {
+            Some(value) => value,
+            None => {
+                self.spec(name)
+                    .unwrap_or_else(|| panic!("parameter {} is not defined", name))
+                    .default
+            },
+        }
+    }
+
+    pub fn parse<T: FromStr>(&self, name: &str) -> Result<T, T::Err> {
+        self.get(name).parse()
+    }
+}
+
+/// The name of the `part1` and `part2` functions of a day.
+pub const DEFAULT_IMPLEMENTATION: &str = "default";
+
+type PartFunc<D, O> = fn(&D, &Params, &Context) -> PartResult<O>;
+
+/// An alternative way of solving one or both parts of a day.
+pub struct Implementation<D, O1, O2 = O1> {
+    pub name: &'static str,
+    pub part1: Option<PartFunc<D, O1>>,
+    pub part2: Option<PartFunc<D, O2>>,
+}
+
+impl<D, O1, O2> Implementation<D, O1, O2> {
+    fn solves(&self, part: Part) -> bool {
+        match part {
+            Part::First => self.part1.is_some(),
+            Part::Second => self.part2.is_some(),
+        }
+    }
+}
+
+/// The functions of a day, where the two parts can give different types of
+/// answer.
+pub struct DayCalc<D: 'static, O1: 'static, O2: 'static = O1> {
     pub parse: fn(&str) -> ParseResult<D>,
-    pub part1: fn(&D) -> PartOutput<O>,
-    pub part2: fn(&D) -> PartOutput<O>,
+    pub part1: PartFunc<D, O1>,
+    pub part2: PartFunc<D, O2>,
+    pub alternatives: &'static [Implementation<D, O1, O2>],
+}
+
+// Every field is a function pointer or a static slice, so the functions can
+// be copied into a session without requiring `D: Copy`.
+impl<D, O1, O2> Clone for DayCalc<D, O1, O2> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D, O1, O2> Copy for DayCalc<D, O1, O2> {}
+
+impl<D, O1, O2> DayCalc<D, O1, O2> {
+    fn get_alternative(&self, name: &str) -> Option<&Implementation<D, O1, O2>> {
+        self.alternatives
+            .iter()
+            .find(|implementation| implementation.name == name)
+    }
+
+    fn get_part1(&self, name: &str) -> Option<PartFunc<D, O1>> {
+        if name == DEFAULT_IMPLEMENTATION {
+            return Some(self.part1);
+        }
+        self.get_alternative(name)?.part1
+    }
+
+    fn get_part2(&self, name: &str) -> Option<PartFunc<D, O2>> {
+        if name == DEFAULT_IMPLEMENTATION {
+            return Some(self.part2);
+        }
+        self.get_alternative(name)?.part2
+    }
+}
+
+/// An example input, with the answers the puzzle gives for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Example {
+    pub input: &'static str,
+    pub answers: [Option<&'static str>; 2],
+}
+
+impl Example {
+    /// An example without known answers.
+    pub const fn new(input: &'static str) -> Self {
+        Self {
+            input,
+            answers: [None, None],
+        }
+    }
+
+    pub const fn answers(self, part1: &'static str, part2: &'static str) -> Self {
+        Self {
+            answers: [Some(part1), Some(part2)],
+            ..self
+        }
+    }
+
+    pub const fn answer(self, part: Part, answer: &'static str) -> Self {
+        let mut answers = self.answers;
+        match part {
+            Part::First => answers[0] = Some(answer),
+            Part::Second => answers[1] = Some(answer),
+        }
+        Self { answers, ..self }
+    }
+
+    /// The answer the puzzle gives for a part, if any.
+    pub fn answer_to(&self, part: Part) -> Option<&'static str> {
+        match part {
+            Part::First => self.answers[0],
+            Part::Second => self.answers[1],
+        }
+    }
 }
 
 pub struct Examples<const C: usize, const F: usize, const S: usize> {
-    pub common: [&'static str; C],
-    pub part1: [&'static str; F],
-    pub part2: [&'static str; S],
+    pub common: [Example; C],
+    pub part1: [Example; F],
+    pub part2: [Example; S],
 }
 impl Examples<1, 0, 0> {
-    const fn single(include_str: &'static str) -> Self {
+    const fn single(example: Example) -> Self {
         Examples {
-            common: [include_str],
+            common: [example],
             part1: [],
             part2: [],
         }
     }
 }
 impl Examples<0, 1, 1> {
-    const fn pair(first: &'static str, second: &'static str) -> Self {
+    const fn pair(first: Example, second: Example) -> Self {
         Examples {
             common: [],
             part1: [first],
@@ -102,10 +479,20 @@ impl Examples<0, 1, 1> {
     }
 }
 
-pub struct Day<D, O, const C: usize, const F: usize, const S: usize> {
+/// A day of the calendar, where `O2` is the type of the answer to part 2 if it
+/// differs from the answer to part 1.
+pub struct Day<
+    D: 'static,
+    O1: 'static,
+    const C: usize,
+    const F: usize,
+    const S: usize,
+    O2: 'static = O1,
+> {
     pub title: &'static str,
     pub display: (&'static str, &'static str),
-    pub calc: DayCalc<D, O>,
+    pub params: &'static [Param],
+    pub calc: DayCalc<D, O1, O2>,
     pub examples: Examples<C, F, S>,
 }
 
@@ -118,9 +505,14 @@ pub trait Printable {
     fn get_display(&self) -> (&'static str, &'static str);
     fn get_title(&self) -> &'static str;
     fn get_examples(&self) -> PrimaryExample;
+    /// Every example, with the part it is for, or `None` if it is for both.
+    fn get_all_examples(&self) -> Vec<(Option<Part>, Example)>;
+    fn get_params(&self) -> &'static [Param];
 }
 
-impl<D, O, const C: usize, const F: usize, const S: usize> Printable for Day<D, O, C, F, S> {
+impl<D, O1, const C: usize, const F: usize, const S: usize, O2> Printable
+    for Day<D, O1, C, F, S, O2>
+{
     fn get_display(&self) -> (&'static str, &'static str) {
         self.display
     }
@@ -133,91 +525,189 @@ impl<D, O, const C: usize, const F: usize, const S: usize> Printable for Day<D,
             .part1
             .first()
             .or(self.examples.common.first())
-            .unwrap();
+            .unwrap()
+            .input;
         let second = self
             .examples
             .part2
             .first()
             .or(self.examples.common.first())
-            .unwrap();
+            .unwrap()
+            .input;
         if first == second {
             PrimaryExample::Same(first)
         } else {
             PrimaryExample::Different([first, second])
         }
     }
+    fn get_all_examples(&self) -> Vec<(Option<Part>, Example)> {
+        let common = self.examples.common.iter().map(|&example| (None, example));
+        let part1 = self
+            .examples
+            .part1
+            .iter()
+            .map(|&example| (Some(Part::First), example));
+        let part2 = self
+            .examples
+            .part2
+            .iter()
+            .map(|&example| (Some(Part::Second), example));
+        common.chain(part1).chain(part2).collect()
+    }
+    fn get_params(&self) -> &'static [Param] {
+        self.params
+    }
+}
+
+type DayResult = CalcResult<(String, String)>;
+type SessionResult = std::result::Result<String, SolveError>;
+
+/// Parsed input for a day, which can solve either part as often as needed
+/// without parsing again, from any thread.
+pub trait Session: Send + Sync {
+    fn run(&self, part: Part, params: &Params, context: &Context) -> SessionResult;
+    /// Solve a part with a named implementation, or `None` if the part has no
+    /// implementation with that name.
+    fn run_with(
+        &self,
+        part: Part,
+        implementation: &str,
+        params: &Params,
+        context: &Context,
+    ) -> Option<SessionResult>;
+    /// The parsed input, for callers that know the type a day parses into.
+    fn data(&self) -> &dyn Any;
 }
 
-type DayResult = ParseResult<(String, String)>;
+struct DaySession<D: 'static, O1: 'static, O2: 'static> {
+    data: D,
+    calc: DayCalc<D, O1, O2>,
+}
+
+impl<D: 'static + Send + Sync, O1: std::fmt::Display, O2: std::fmt::Display> Session
+    for DaySession<D, O1, O2>
+{
+    fn run(&self, part: Part, params: &Params, context: &Context) -> SessionResult {
+        self.run_with(part, DEFAULT_IMPLEMENTATION, params, context)
+            .expect("every part has a default implementation")
+    }
+    fn run_with(
+        &self,
+        part: Part,
+        implementation: &str,
+        params: &Params,
+        context: &Context,
+    ) -> Option<SessionResult> {
+        Some(match part {
+            Part::First => self.calc.get_part1(implementation)?(&self.data, params, context)
+                .map(|output| output.answer.to_string()),
+            Part::Second => self.calc.get_part2(implementation)?(&self.data, params, context)
+                .map(|output| output.answer.to_string()),
+        })
+    }
+    fn data(&self) -> &dyn Any {
+        &self.data
+    }
+}
 
 pub trait Calculable {
-    fn calc(&self, part: Part, input: &str) -> ParseResult<String>;
-    fn both(&self, input: &str) -> DayResult;
-    fn get_both_func(&self) -> Rc<dyn Fn(&str) -> DayResult>;
+    /// Parse an input once, to solve its parts later.
+    fn session(&self, input: &str) -> ParseResult<Box<dyn Session>>;
+    /// The names of every implementation of a part, starting with the default.
+    fn implementations(&self, part: Part) -> Vec<&'static str>;
+    fn calc(
+        &self,
+        part: Part,
+        input: &str,
+        params: &Params,
+        context: &Context,
+    ) -> CalcResult<String> {
+        Ok(self.session(input)?.run(part, params, context)?)
+    }
+    /// Calculate a part with a named implementation, or `None` if the part has
+    /// no implementation with that name.
+    fn calc_with(
+        &self,
+        part: Part,
+        implementation: &str,
+        input: &str,
+        params: &Params,
+        context: &Context,
+    ) -> Option<CalcResult<String>> {
+        if !self.implementations(part).contains(&implementation) {
+            return None;
+        }
+        Some(
+            self.session(input)
+                .map_err(DayError::from)
+                .and_then(|session| {
+                    Ok(session
+                        .run_with(part, implementation, params, context)
+                        .expect("the implementation was listed")?)
+                }),
+        )
+    }
+    fn both(&self, input: &str, params: &Params, context: &Context) -> DayResult {
+        let session = self.session(input)?;
+        Ok((
+            session.run(Part::First, params, context)?,
+            session.run(Part::Second, params, context)?,
+        ))
+    }
 }
 
 impl<
-        D: 'static,
-        O: 'static + std::fmt::Display,
+        D: 'static + Send + Sync,
+        O1: 'static + std::fmt::Display,
         const C: usize,
         const F: usize,
         const S: usize,
-    > Calculable for Day<D, O, C, F, S>
+        O2: 'static + std::fmt::Display,
+    > Calculable for Day<D, O1, C, F, S, O2>
 {
-    fn calc(&self, part: Part, input: &str) -> ParseResult<String> {
-        let parse = self.calc.parse;
-        let input = parse(input)?;
-        Ok(match part {
-            Part::First => (self.calc.part1)(&input).answer.to_string(),
-            Part::Second => (self.calc.part2)(&input).answer.to_string(),
-        })
+    fn session(&self, input: &str) -> ParseResult<Box<dyn Session>> {
+        Ok(Box::new(DaySession {
+            data: (self.calc.parse)(input)?,
+            calc: self.calc,
+        }))
     }
-    fn both(&self, input: &str) -> DayResult {
-        let parse = self.calc.parse;
-        let part1 = self.calc.part1;
-        let part2 = self.calc.part2;
-        let input = parse(input)?;
-        Ok((
-            part1(&input).answer.to_string(),
-            part2(&input).answer.to_string(),
-        ))
-    }
-    fn get_both_func(&self) -> Rc<dyn Fn(&str) -> DayResult> {
-        let parse = self.calc.parse;
-        let part1 = self.calc.part1;
-        let part2 = self.calc.part2;
-        Rc::new(move |input: &str| {
-            let input = parse(input)?;
-            Ok((
-                part1(&input).answer.to_string(),
-                part2(&input).answer.to_string(),
-            ))
-        })
+    fn implementations(&self, part: Part) -> Vec<&'static str> {
+        std::iter::once(DEFAULT_IMPLEMENTATION)
+            .chain(
+                self.calc
+                    .alternatives
+                    .iter()
+                    .filter(|implementation| implementation.solves(part))
+                    .map(|implementation| implementation.name),
+            )
+            .collect()
     }
 }
 
-pub trait DayTrait: Printable + Calculable + Send {}
+/// A day that can be shared between threads, such as the workers of a pool.
+pub trait DayTrait: Printable + Calculable + Send + Sync {}
 
 impl<
-        D: 'static,
-        O: 'static + std::fmt::Display,
+        D: 'static + Send + Sync,
+        O1: 'static + std::fmt::Display,
         const C: usize,
         const F: usize,
         const S: usize,
-    > DayTrait for Day<D, O, C, F, S>
+        O2: 'static + std::fmt::Display,
+    > DayTrait for Day<D, O1, C, F, S, O2>
 {
 }
 
-pub fn get_days() -> BTreeMap<usize, Box<dyn DayTrait + 'static>> {
-    let mut days: BTreeMap<usize, Box<dyn DayTrait + 'static>> = BTreeMap::new();
-    days.insert(1, Box::new(day01::DAY));
-    days.insert(2, Box::new(day02::DAY));
-    days.insert(3, Box::new(day03::DAY));
-    days.insert(4, Box::new(day04::DAY));
-    days.insert(5, Box::new(day05::DAY));
-    days.insert(6, Box::new(day06::DAY));
-    days.insert(7, Box::new(day07::DAY));
-    days.insert(8, Box::new(day08::DAY));
+pub fn get_days() -> BTreeMap<usize, Arc<dyn DayTrait + 'static>> {
+    let mut days: BTreeMap<usize, Arc<dyn DayTrait + 'static>> = BTreeMap::new();
+    days.insert(1, Arc::new(day01::DAY));
+    days.insert(2, Arc::new(day02::DAY));
+    days.insert(3, Arc::new(day03::DAY));
+    days.insert(4, Arc::new(day04::DAY));
+    days.insert(5, Arc::new(day05::DAY));
+    days.insert(6, Arc::new(day06::DAY));
+    days.insert(7, Arc::new(day07::DAY));
+    days.insert(8, Arc::new(day08::DAY));
     days
 }
 
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use std::str::FromStr;
 
+use crate::{ParseError, ParseResult};
+
 const DELIMITERS: [&str; 6] = ["\n\n", "\n", ",", " ", ":", "-"];
 
 pub fn read_vec1<T: FromStr>(input: &str) -> Result<Vec<T>, T::Err> {
@@ -18,6 +20,15 @@ pub fn read_vec1<T: FromStr>(input: &str) -> Result<Vec<T>, T::Err> {
         .collect::<Result<Vec<T>, _>>()
 }
 
+/// Parse each line of the input, placing any error on the line it is on.
+pub fn read_lines<T: FromStr<Err = ParseError>>(input: &str) -> ParseResult<Vec<T>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| line.parse().map_err(|e: ParseError| e.at_line(index + 1)))
+        .collect()
+}
+
 pub fn read_vec2<T: FromStr>(input: &str) -> Result<Vec<Vec<T>>, T::Err> {
     log::trace!("input: {input}");
     let found_delims: Vec<&str> = DELIMITERS
@@ -0,0 +1,53 @@
+use aho_corasick::AhoCorasick;
+
+/// Finds every occurrence of a set of patterns in a single pass over a string,
+/// including occurrences that overlap, e.g. both `two` and `one` in `twone`.
+///
+/// Each pattern is associated with a value that is returned when it matches.
+#[derive(Debug, Clone)]
+pub struct Scanner<T> {
+    automaton: AhoCorasick,
+    values: Vec<T>,
+}
+
+impl<T: Copy> Scanner<T> {
+    pub fn new<P: AsRef<str>>(patterns: impl IntoIterator<Item = (P, T)>) -> Self {
+        let (patterns, values): (Vec<P>, Vec<T>) = patterns.into_iter().unzip();
+        Self {
+            automaton: AhoCorasick::new(patterns.iter().map(AsRef::as_ref)).unwrap(),
+            values,
+        }
+    }
+
+    /// The values of the match starting first and the match ending last.
+    pub fn first_last(&self, haystack: &str) -> Option<(T, T)> {
+        let mut matches = self.automaton.find_overlapping_iter(haystack);
+        let first_match = matches.next()?;
+        let (first, last) = matches.fold((first_match, first_match), |(first, last), m| {
+            (
+                if m.start() < first.start() { m } else { first },
+                if m.end() > last.end() { m } else { last },
+            )
+        });
+        Some((
+            self.values[first.pattern().as_usize()],
+            self.values[last.pattern().as_usize()],
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_first_last() {
+        let scanner = Scanner::new([("one", 1), ("two", 2), ("1", 1), ("2", 2)]);
+        assert_eq!(scanner.first_last("xtwone"), Some((2, 1)));
+        assert_eq!(scanner.first_last("a1b"), Some((1, 1)));
+        assert_eq!(scanner.first_last("onetwo2one"), Some((1, 1)));
+        assert_eq!(scanner.first_last("three"), None);
+    }
+}
@@ -1,43 +1,246 @@
+#[cfg(test)]
+use crate::{Calculable, Context, DayTrait, Params, Part};
+
+/// Check that every implementation of a part gives the same answer as the
+/// default implementation for an input.
+#[cfg(test)]
+pub(crate) fn assert_implementations_agree<T: Calculable + ?Sized>(
+    day: &T,
+    part: Part,
+    input: &str,
+    params: &Params,
+) {
+    let expected = day.calc(part, input, params, &Context::default()).unwrap();
+    for implementation in day.implementations(part) {
+        assert_eq!(
+            day.calc_with(part, implementation, input, params, &Context::default())
+                .unwrap()
+                .unwrap(),
+            expected,
+            "{part:?} implementation {implementation} disagrees with the default"
+        );
+    }
+}
+
+/// Check the answers to every example of a day whose answers are known, and
+/// that each part has an answered example.
+#[cfg(test)]
+pub(crate) fn assert_examples_answered<T: DayTrait + ?Sized>(day_num: usize, day: &T) {
+    let params = Params::new(day.get_params());
+    for part in [Part::First, Part::Second] {
+        let answered: Vec<_> = day
+            .get_all_examples()
+            .into_iter()
+            .filter(|(example_part, _example)| match example_part {
+                Some(example_part) => *example_part == part,
+                None => true,
+            })
+            .filter_map(|(_part, example)| Some((example.input, example.answer_to(part)?)))
+            .collect();
+        assert!(
+            !answered.is_empty(),
+            "an answer should be provided for day {day_num} {part:?}"
+        );
+        for (input, expected) in answered {
+            assert_eq!(
+                day.calc(part, input, &params, &Context::default()).unwrap(),
+                expected,
+                "day {day_num} {part:?} example mismatch"
+            );
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use test_log::test;
+    use std::sync::Arc;
 
-    use crate::{get_days, Part, PrimaryExample};
+    use test_log::test;
 
-    const EXAMPLE_ANSWERS: [[&'static str; 2]; 7] = [
-        ["142", "281"],
-        ["8", "2286"],
-        ["4361", "467835"],
-        ["13", "30"],
-        ["35", "46"],
-        ["288", "71503"],
-        ["6440", ""],
-    ];
+    use super::{assert_examples_answered, assert_implementations_agree};
+    use crate::{
+        day02, get_days, get_input, Context, Day, DayCalc, DayError, DayTrait, Example, Examples,
+        Implementation, Params, ParseResult, Part, PartOutput, PartResult, PrimaryExample,
+    };
 
     #[test]
     fn test_days_examples() {
-        let days = get_days();
-        for (day_num, day) in days {
-            let (part1, part2) = match day.get_examples() {
-                PrimaryExample::Same(example) => day.both(example).unwrap(),
-                PrimaryExample::Different([first, second]) => (
-                    day.calc(Part::First, first).unwrap(),
-                    day.calc(Part::Second, second).unwrap(),
-                ),
+        for (day_num, day) in get_days() {
+            assert_examples_answered(day_num, day.as_ref());
+        }
+    }
+
+    #[test]
+    fn test_days_implementations() {
+        for (day_num, day) in get_days() {
+            if day.implementations(Part::First).len() == 1
+                && day.implementations(Part::Second).len() == 1
+            {
+                continue;
+            }
+            let params = Params::new(day.get_params());
+            let [first, second] = match day.get_examples() {
+                PrimaryExample::Same(example) => [example, example],
+                PrimaryExample::Different(examples) => examples,
             };
-            let expected = EXAMPLE_ANSWERS
-                .get(day_num - 1)
-                .expect(&format!("answer should be provided for day {}", day_num));
-            let expected_part1 = expected[0];
-            let expected_part2 = expected[1];
-            assert_eq!(
-                part1, expected_part1,
-                "day {day_num} part 1 example mismatch"
-            );
-            assert_eq!(
-                part2, expected_part2,
-                "day {day_num} part 2 example mismatch"
+            assert_implementations_agree(day.as_ref(), Part::First, first, &params);
+            assert_implementations_agree(day.as_ref(), Part::Second, second, &params);
+            let input = get_input(day_num);
+            assert_implementations_agree(day.as_ref(), Part::First, &input, &params);
+            assert_implementations_agree(day.as_ref(), Part::Second, &input, &params);
+        }
+    }
+
+    struct Lines(Vec<String>);
+
+    fn parse_lines(input: &str) -> ParseResult<Lines> {
+        Ok(Lines(input.lines().map(str::to_owned).collect()))
+    }
+
+    fn count_lines(lines: &Lines, _params: &Params, _context: &Context) -> PartResult<usize> {
+        Ok(PartOutput {
+            answer: lines.0.len(),
+        })
+    }
+
+    fn longest_line(lines: &Lines, _params: &Params, _context: &Context) -> PartResult<String> {
+        Ok(PartOutput {
+            answer: lines
+                .0
+                .iter()
+                .max_by_key(|line| line.len())
+                .cloned()
+                .unwrap_or_default(),
+        })
+    }
+
+    fn first_line(lines: &Lines, _params: &Params, _context: &Context) -> PartResult<String> {
+        Ok(PartOutput {
+            answer: lines.0.first().cloned().unwrap_or_default(),
+        })
+    }
+
+    /// A day answering part 1 with a number and part 2 with text.
+    const MIXED_DAY: Day<Lines, usize, 1, 0, 0, String> = Day {
+        title: "Mixed",
+        display: ("{answer} lines", "the longest is {answer}"),
+        params: &[],
+        calc: DayCalc {
+            parse: parse_lines,
+            part1: count_lines,
+            part2: longest_line,
+            alternatives: &[Implementation {
+                name: "first",
+                part1: None,
+                part2: Some(first_line),
+            }],
+        },
+        examples: Examples::single(Example::new("a\nccc\nbb").answers("3", "ccc")),
+    };
+
+    #[test]
+    fn test_mixed_outputs() {
+        let day: Box<dyn DayTrait> = Box::new(MIXED_DAY);
+        let params = Params::new(day.get_params());
+        assert_eq!(
+            day.both("a\nccc\nbb", &params, &Context::default())
+                .unwrap(),
+            (String::from("3"), String::from("ccc"))
+        );
+        assert_eq!(day.implementations(Part::First), vec!["default"]);
+        assert_eq!(day.implementations(Part::Second), vec!["default", "first"]);
+        assert!(day
+            .calc_with(Part::First, "first", "a", &params, &Context::default())
+            .is_none());
+        assert_eq!(
+            day.calc_with(
+                Part::Second,
+                "first",
+                "a\nccc",
+                &params,
+                &Context::default()
             )
+            .unwrap()
+            .unwrap(),
+            "a"
+        );
+    }
+
+    #[test]
+    fn test_day_errors() {
+        let days = get_days();
+        let day = &days[&8];
+        let params = Params::new(day.get_params());
+        assert!(matches!(
+            day.calc(
+                Part::First,
+                "LR\n\nAAA = (AAA, AAA)",
+                &params,
+                &Context::default()
+            ),
+            Err(DayError::Solve(_))
+        ));
+        assert!(matches!(
+            day.both("LR\n\nAAA = (BBB, BBB)", &params, &Context::default()),
+            Err(DayError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_session_sweep() {
+        let days = get_days();
+        let day = &days[&2];
+        let session = day.session(day02::DAY.examples.common[0].input).unwrap();
+        let mut params = Params::new(day.get_params());
+        let answers: Vec<String> = ["12", "20"]
+            .into_iter()
+            .map(|red| {
+                params.set("red", red).unwrap();
+                session
+                    .run(Part::First, &params, &Context::default())
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(answers, ["8", "11"]);
+        assert_eq!(
+            session
+                .run(Part::Second, &params, &Context::default())
+                .unwrap(),
+            "2286"
+        );
+        assert!(session.data().downcast_ref::<day02::Records>().is_some());
+    }
+
+    #[test]
+    fn test_days_concurrently() {
+        let days = Arc::new(get_days());
+        let threads: Vec<_> = (0..4)
+            .flat_map(|_| days.keys().copied().collect::<Vec<_>>())
+            .map(|day_num| {
+                let days = Arc::clone(&days);
+                std::thread::spawn(move || {
+                    assert_examples_answered(day_num, days[&day_num].as_ref())
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
         }
+
+        // One session can also be solved from several threads at once.
+        let day = &days[&7];
+        let params = Params::new(day.get_params());
+        let input = get_input(7);
+        let session = day.session(&input).unwrap();
+        let answers = std::thread::scope(|scope| {
+            let (session, params) = (&session, &params);
+            [Part::First, Part::Second]
+                .map(|part| {
+                    scope.spawn(move || session.run(part, params, &Context::default()).unwrap())
+                })
+                .map(|thread| thread.join().unwrap())
+        });
+        let (part1, part2) = day.both(&input, &params, &Context::default()).unwrap();
+        assert_eq!(answers, [part1, part2]);
     }
 }
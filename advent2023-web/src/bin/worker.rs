@@ -0,0 +1,19 @@
+use advent2023_web::solver::{report_panic, SolveWorker};
+
+fn main() {
+    console_log::init_with_level(log::Level::Info).expect("logging failed");
+    // The worker stops at a panic, so tell the page rather than leave it waiting.
+    std::panic::set_hook(Box::new(|info| {
+        log::error!("{}", info);
+        let message = match info.payload().downcast_ref::<&str>() {
+            Some(message) => (*message).to_owned(),
+            None => info
+                .payload()
+                .downcast_ref::<String>()
+                .cloned()
+                .unwrap_or_else(|| info.to_string()),
+        };
+        report_panic(message);
+    }));
+    SolveWorker::register();
+}
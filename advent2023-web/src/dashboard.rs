@@ -0,0 +1,336 @@
+use std::rc::Rc;
+use std::time::Duration;
+
+use advent2023_lib::get_days;
+use advent2023_web::solver::{
+    part_num, Outcome, ParseFailure, SolveBridge, SolveRequest, SolveResponse,
+};
+use yew::prelude::*;
+
+use crate::examples::example_label;
+use crate::router::{Link, Route};
+use crate::storage;
+
+/// An input of a day, and how solving it went.
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    request: SolveRequest,
+    /// Where the input came from.
+    source: String,
+    /// The answers the puzzle gives, if the input is an example.
+    expected: [Option<String>; 2],
+    parse_error: Option<ParseFailure>,
+    results: [Option<(Outcome, Duration)>; 2],
+    /// Whether the worker panicked solving the row, and stopped with it.
+    panicked: bool,
+}
+
+impl Row {
+    fn new(request: SolveRequest, source: String, expected: [Option<String>; 2]) -> Self {
+        Self {
+            request,
+            source,
+            expected,
+            parse_error: None,
+            results: [None, None],
+            panicked: false,
+        }
+    }
+
+    fn wants(&self, part_num: usize) -> bool {
+        match self.request.part_num {
+            Some(wanted) => wanted == part_num,
+            None => true,
+        }
+    }
+
+    fn finished(&self) -> bool {
+        self.parse_error.is_some()
+            || (1..=2).all(|part_num| !self.wants(part_num) || self.results[part_num - 1].is_some())
+    }
+
+    /// Whether a part gave the answer the puzzle gives, if it is known.
+    fn passed(&self, index: usize) -> Option<bool> {
+        match (&self.expected[index], &self.results[index]) {
+            (Some(expected), Some((Outcome::Answer(answer), _elapsed))) => Some(expected == answer),
+            (Some(_expected), Some(_failed)) => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// Every example of every day, and the input saved for it if there is one.
+fn all_rows() -> Vec<Row> {
+    let mut rows = Vec::new();
+    for (day_num, day) in get_days() {
+        for (index, (part, example)) in day.get_all_examples().into_iter().enumerate() {
+            let request = SolveRequest {
+                day_num,
+                part_num: part.map(part_num),
+                input: example.input.to_owned(),
+                params: Vec::new(),
+            };
+            let expected = example.answers.map(|answer| answer.map(str::to_owned));
+            rows.push(Row::new(request, example_label(index, part), expected));
+        }
+        if let Some(saved) = storage::load_input(day_num) {
+            let request = SolveRequest {
+                day_num,
+                part_num: None,
+                input: saved.input,
+                params: saved.params,
+            };
+            rows.push(Row::new(request, saved.source, [None, None]));
+        }
+    }
+    rows
+}
+
+/// The rows of a run of every day. Each row is sent to the worker once the
+/// one before it has finished, so its responses are for the current row.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Board {
+    rows: Vec<Row>,
+    /// The index of the row being solved, if any is.
+    current: Option<usize>,
+}
+
+enum BoardAction {
+    Start(Vec<Row>),
+    Respond(SolveResponse),
+    Cancel,
+}
+
+impl Reducible for Board {
+    type Action = BoardAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut board = (*self).clone();
+        match action {
+            BoardAction::Start(rows) => {
+                board.current = (!rows.is_empty()).then_some(0);
+                board.rows = rows;
+            },
+            BoardAction::Respond(response) => {
+                let Some(current) = board.current else {
+                    return self;
+                };
+                let row = &mut board.rows[current];
+                match response {
+                    SolveResponse::ParseFailed(e) => row.parse_error = Some(e),
+                    SolveResponse::Progress { .. } => return self,
+                    SolveResponse::Solved {
+                        part_num,
+                        outcome,
+                        elapsed,
+                    } => row.results[part_num - 1] = Some((outcome, elapsed)),
+                    SolveResponse::Panicked {
+                        part_num: None,
+                        message,
+                        ..
+                    } => {
+                        row.parse_error = Some(ParseFailure {
+                            kind: String::from("Panic"),
+                            message: format!("the worker panicked, {}", message),
+                            line: None,
+                        });
+                        row.panicked = true;
+                    },
+                    SolveResponse::Panicked {
+                        part_num: Some(part_num),
+                        message,
+                        elapsed,
+                    } => {
+                        for other in 1..=2 {
+                            if !row.wants(other) {
+                                continue;
+                            }
+                            let result = &mut row.results[other - 1];
+                            if other == part_num {
+                                *result = Some((
+                                    Outcome::Failed(format!("panicked, {}", message)),
+                                    elapsed,
+                                ));
+                            } else if result.is_none() {
+                                let reason = format!("not run, part {} panicked", part_num);
+                                *result = Some((Outcome::Failed(reason), Duration::ZERO));
+                            }
+                        }
+                        row.panicked = true;
+                    },
+                }
+                if row.finished() {
+                    board.current = Some(current + 1).filter(|&next| next < board.rows.len());
+                }
+            },
+            BoardAction::Cancel => board.current = None,
+        }
+        Rc::new(board)
+    }
+}
+
+fn result_cell(row: &Row, index: usize, waiting: &'static str) -> Html {
+    if !row.wants(index + 1) {
+        return html! { <td>{"—"}</td> };
+    }
+    let (text, elapsed) = match &row.results[index] {
+        None => return html! { <td class="waiting">{waiting}</td> },
+        Some((Outcome::Answer(answer), elapsed)) => (answer.clone(), elapsed),
+        Some((Outcome::Failed(e), elapsed)) => (format!("no answer, {}", e), elapsed),
+        Some((Outcome::TimedOut, elapsed)) => ("timed out".to_owned(), elapsed),
+    };
+    let mark = match (row.passed(index), &row.expected[index]) {
+        (Some(true), _) => html! { <span class="expected">{" ✔"}</span> },
+        (Some(false), Some(expected)) => html! {
+            <span class="unexpected">{format!(" ✘ expected {}", expected)}</span>
+        },
+        _ => html! {},
+    };
+    html! {
+        <td>
+            {text}{mark}
+            <span class="elapsed">{format!(" ({:.2?})", elapsed)}</span>
+        </td>
+    }
+}
+
+/// Solve every day, one input after the other, and tabulate the answers.
+#[function_component]
+pub fn Dashboard() -> Html {
+    let board = use_reducer(Board::default);
+    let bridge = use_mut_ref(|| None::<SolveBridge>);
+
+    let spawn = {
+        let dispatcher = board.dispatcher();
+        move || {
+            let dispatcher = dispatcher.clone();
+            SolveBridge::spawn(move |response| dispatcher.dispatch(BoardAction::Respond(response)))
+                .map_err(|e| log::error!("{}", e))
+                .ok()
+        }
+    };
+
+    // Each run has a worker of its own, and dropping the worker of the run
+    // before terminates it.
+    let on_run = {
+        let board = board.clone();
+        let bridge = bridge.clone();
+        let spawn = spawn.clone();
+        Callback::from(move |_| {
+            *bridge.borrow_mut() = spawn();
+            board.dispatch(BoardAction::Start(all_rows()));
+        })
+    };
+
+    // Send each row as it becomes current. A worker that panicked answers no
+    // more requests, so the row after it has a new one.
+    {
+        let board = board.clone();
+        let bridge = bridge.clone();
+        use_effect_with(board.current, move |&current| {
+            let Some(current) = current else {
+                return;
+            };
+            let mut bridge = bridge.borrow_mut();
+            if bridge.is_none() || (current > 0 && board.rows[current - 1].panicked) {
+                *bridge = spawn();
+            }
+            match bridge.as_ref() {
+                Some(worker) => worker.send(board.rows[current].request.clone()),
+                None => board.dispatch(BoardAction::Cancel),
+            }
+        });
+    }
+
+    let on_cancel = {
+        let board = board.clone();
+        let bridge = bridge.clone();
+        Callback::from(move |_| {
+            // Dropping the worker terminates it, stopping the part it runs.
+            bridge.borrow_mut().take();
+            board.dispatch(BoardAction::Cancel);
+        })
+    };
+
+    let finished = board.rows.iter().filter(|row| row.finished()).count();
+    let (passed, failed) = board
+        .rows
+        .iter()
+        .flat_map(|row| (0..2).filter_map(|index| row.passed(index)))
+        .fold((0, 0), |(passed, failed), ok| {
+            if ok {
+                (passed + 1, failed)
+            } else {
+                (passed, failed + 1)
+            }
+        });
+    html! {
+        <section class="dashboard">
+            <div class="row">
+                <h2 class="row-item">{"Every day"}</h2>
+                <div class="row-item">
+                    <button type="button" onclick={on_run} disabled={board.current.is_some()}>{ "▶ Run all" }</button>
+                    <button type="button" onclick={on_cancel} disabled={board.current.is_none()}>{ "■ Cancel" }</button>
+                </div>
+            </div>
+            {
+                if board.rows.is_empty() {
+                    html! {
+                        <p>{"Runs the examples of every day, and the inputs saved from running days."}</p>
+                    }
+                } else {
+                    html! {
+                        <p>
+                            {format!("{} of {} inputs solved, ", finished, board.rows.len())}
+                            <span class="expected">{format!("{} as expected", passed)}</span>
+                            {", "}
+                            <span class={if failed > 0 { "unexpected" } else { "" }}>{format!("{} not", failed)}</span>
+                        </p>
+                    }
+                }
+            }
+            <table>
+                <thead>
+                    <tr>
+                        <th>{"Day"}</th>
+                        <th>{"Input"}</th>
+                        <th>{"Part 1"}</th>
+                        <th>{"Part 2"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                {
+                    for board.rows.iter().enumerate().map(|(index, row)| {
+                        let waiting = match board.current {
+                            Some(current) if current == index => "running...",
+                            Some(_current) => "waiting",
+                            None => "not run",
+                        };
+                        html! {
+                            <tr>
+                                <td>
+                                    <Link route={Route::Day(row.request.day_num)}>{row.request.day_num}</Link>
+                                </td>
+                                <td>{&row.source}</td>
+                                {
+                                    match &row.parse_error {
+                                        Some(e) => html! {
+                                            <td colspan="2" class="unexpected">{format!("parsing failed, {}", e)}</td>
+                                        },
+                                        None => html! {
+                                            <>
+                                                {result_cell(row, 0, waiting)}
+                                                {result_cell(row, 1, waiting)}
+                                            </>
+                                        },
+                                    }
+                                }
+                            </tr>
+                        }
+                    })
+                }
+                </tbody>
+            </table>
+        </section>
+    }
+}
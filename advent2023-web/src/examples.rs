@@ -0,0 +1,102 @@
+use advent2023_lib::{Example, Part};
+use yew::prelude::*;
+
+use crate::router::{Link, Route};
+
+/// Describe which parts an example is for.
+pub fn example_label(index: usize, part: Option<Part>) -> String {
+    match part {
+        None => format!("Example {}", index + 1),
+        Some(Part::First) => format!("Example {} for part 1", index + 1),
+        Some(Part::Second) => format!("Example {} for part 2", index + 1),
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ExampleProps {
+    pub day_num: usize,
+    pub index: usize,
+    pub part: Option<Part>,
+    pub example: Example,
+    /// Whether the example was linked to.
+    pub selected: bool,
+    pub run_callback: Callback<(usize, Option<Part>, Example)>,
+}
+
+#[function_component]
+pub fn ExampleView(props: &ExampleProps) -> Html {
+    let show_input = use_state(|| false);
+    {
+        let show_input = show_input.clone();
+        use_effect_with(props.selected, move |&selected| {
+            if selected {
+                show_input.set(true);
+            }
+        });
+    }
+
+    let on_collapse = {
+        let show_input = show_input.clone();
+        Callback::from(move |_| {
+            show_input.set(!*show_input);
+        })
+    };
+
+    let on_run = {
+        let (index, part, example) = (props.index, props.part, props.example);
+        let run_callback = props.run_callback.clone();
+        Callback::from(move |_| run_callback.emit((index, part, example)))
+    };
+
+    let input = props.example.input;
+    let multiline = input.lines().count() > 1;
+    html! {
+        <>
+            <div class={classes!("row", "row-reverse", props.selected.then_some("selected"))}>
+                <div class="row-item day-run">
+                    <button type="button" onclick={on_run}>{ "▶ Run..." }</button>
+                </div>
+                <Link class="row-item example-link" route={Route::Example { day_num: props.day_num, example_num: props.index + 1 }}>{"🔗"}</Link>
+                <div class="row-item day-collapse">
+                    <h5 class={if multiline {"button"} else {"button disabled"}} onclick={on_collapse}>
+                        {if multiline && *show_input { "▼ " } else { "▬ " }}
+                        {example_label(props.index, props.part)}{": "}
+                    </h5>
+                </div>
+            </div>
+            {
+                if *show_input {
+                    html! {
+                        <pre>{input}</pre>
+                    }
+                } else {
+                    html! {
+                        <pre class={if multiline {"collapsed"} else {""}}>
+                            {input.lines().take(2).collect::<Vec<_>>().join("\n")}
+                        </pre>
+                    }
+                }
+            }
+        </>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ExampleListProps {
+    pub day_num: usize,
+    pub examples: Vec<(Option<Part>, Example)>,
+    /// The index of the example that was linked to, if any.
+    pub selected: Option<usize>,
+    pub run_callback: Callback<(usize, Option<Part>, Example)>,
+}
+
+#[function_component]
+pub fn ExampleList(props: &ExampleListProps) -> Html {
+    html! {
+        {
+            for props.examples.iter().enumerate().map(|(index, &(part, example))| html! {
+                <ExampleView day_num={props.day_num} {index} {part} {example} selected={props.selected == Some(index)} run_callback={props.run_callback.clone()} />
+            })
+        }
+    }
+}
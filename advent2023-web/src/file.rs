@@ -1,14 +1,83 @@
-use std::str::from_utf8;
+use std::fmt::Display;
 
-use gloo_file::callbacks::read_as_bytes;
-use gloo_file::File;
-use web_sys::HtmlInputElement;
+use gloo_file::callbacks::{read_as_bytes, FileReader};
+use gloo_file::FileList;
+use web_sys::{HtmlInputElement, HtmlTextAreaElement};
 use yew::prelude::*;
 
+/// The text of a file chosen or dropped by the user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedFile {
+    pub name: String,
+    pub text: String,
+}
+
+/// Why an input could not be loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadError {
+    NoFile,
+    MultipleFiles(usize),
+    NotUtf8(String),
+    Read(String),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoFile => write!(f, "no file was given"),
+            Self::MultipleFiles(count) => {
+                write!(f, "{} files were given, but a day takes one", count)
+            },
+            Self::NotUtf8(name) => write!(f, "{} is not a UTF-8 text file", name),
+            Self::Read(e) => write!(f, "the file could not be read: {}", e),
+        }
+    }
+}
+
+pub type LoadResult = Result<LoadedFile, LoadError>;
+
+/// The text with the blank lines at its end dropped, which the days do not
+/// expect, ending in a single newline as the puzzle inputs do.
+fn trim_input(text: &str) -> String {
+    let mut input = text.trim_end().to_owned();
+    input.push('\n');
+    input
+}
+
+/// Start reading the only file of a list as text. The reading stops if the
+/// returned reader is dropped.
+fn read_single_file(
+    files: Option<web_sys::FileList>,
+    callback: Callback<LoadResult>,
+) -> Result<FileReader, LoadError> {
+    let Some(files) = files.map(FileList::from) else {
+        return Err(LoadError::NoFile);
+    };
+    let file = match &files[..] {
+        [] => return Err(LoadError::NoFile),
+        [file] => file,
+        files => return Err(LoadError::MultipleFiles(files.len())),
+    };
+    let name = file.name();
+    log::info!("loading file '{}'...", name);
+    Ok(read_as_bytes(file, move |bytes| {
+        let loaded = match bytes {
+            Ok(bytes) => String::from_utf8(bytes)
+                .map(|text| LoadedFile {
+                    name: name.clone(),
+                    text: trim_input(&text),
+                })
+                .map_err(|_e| LoadError::NotUtf8(name)),
+            Err(e) => Err(LoadError::Read(e.to_string())),
+        };
+        callback.emit(loaded)
+    }))
+}
+
 #[derive(Properties, PartialEq)]
 pub struct FileProps {
     pub day_num: usize,
-    pub file_load_callback: Callback<String>,
+    pub file_load_callback: Callback<LoadResult>,
 }
 
 #[function_component]
@@ -19,27 +88,10 @@ pub fn FileUpload(props: &FileProps) -> Html {
         let file_load_callback = props.file_load_callback.clone();
         Callback::from(move |e: Event| {
             let input: HtmlInputElement = e.target_unchecked_into();
-            let mut gloo_files: Vec<File> = Vec::new();
-            if let Some(files) = input.files() {
-                let files = js_sys::try_iter(&files)
-                    .unwrap()
-                    .unwrap()
-                    .map(|v| web_sys::File::from(v.unwrap()))
-                    .map(File::from);
-                gloo_files.extend(files);
-            }
-            if gloo_files.len() > 1 {
-                panic!("more than  one file received")
+            match read_single_file(input.files(), file_load_callback.clone()) {
+                Ok(reader) => file_reader.set(Some(reader)),
+                Err(e) => file_load_callback.emit(Err(e)),
             }
-            let gloo_file = gloo_files.pop().unwrap();
-            let file_name = gloo_file.name();
-            log::info!("loading file '{}'...", file_name);
-            let file_load_callback = file_load_callback.clone();
-            let reader = read_as_bytes(&gloo_file, move |blob_bytes| {
-                let read_string = from_utf8(&blob_bytes.unwrap()).unwrap().to_owned();
-                file_load_callback.emit(read_string)
-            });
-            file_reader.set(Some((file_name, reader)))
         })
     };
     let file_upload_id = format!("file-upload-day-{}", props.day_num);
@@ -56,3 +108,91 @@ pub fn FileUpload(props: &FileProps) -> Html {
         </div>
     }
 }
+
+#[derive(Properties, PartialEq)]
+pub struct DropProps {
+    pub file_load_callback: Callback<LoadResult>,
+    pub class: Classes,
+    pub children: Html,
+}
+
+/// An area that loads a file dropped onto it.
+#[function_component]
+pub fn DropZone(props: &DropProps) -> Html {
+    let file_reader = use_state(|| None::<FileReader>);
+    let dragging = use_state(|| false);
+
+    let on_drag_over = {
+        let dragging = dragging.clone();
+        Callback::from(move |e: DragEvent| {
+            // Dropping is only allowed if the default is prevented.
+            e.prevent_default();
+            dragging.set(true);
+        })
+    };
+
+    let on_drag_leave = {
+        let dragging = dragging.clone();
+        Callback::from(move |_: DragEvent| dragging.set(false))
+    };
+
+    let on_drop = {
+        let dragging = dragging.clone();
+        let file_load_callback = props.file_load_callback.clone();
+        Callback::from(move |e: DragEvent| {
+            e.prevent_default();
+            dragging.set(false);
+            let files = e.data_transfer().and_then(|transfer| transfer.files());
+            match read_single_file(files, file_load_callback.clone()) {
+                Ok(reader) => file_reader.set(Some(reader)),
+                Err(e) => file_load_callback.emit(Err(e)),
+            }
+        })
+    };
+
+    html! {
+        <div
+            class={classes!(props.class.clone(), dragging.then_some("dragging"))}
+            ondragover={on_drag_over}
+            ondragleave={on_drag_leave}
+            ondrop={on_drop}
+        >
+            {props.children.clone()}
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct PasteProps {
+    pub day_num: usize,
+    pub paste_callback: Callback<String>,
+}
+
+/// A text area to paste an input into.
+#[function_component]
+pub fn PasteInput(props: &PasteProps) -> Html {
+    let text = use_state(String::new);
+
+    let on_input = {
+        let text = text.clone();
+        Callback::from(move |e: InputEvent| {
+            let area: HtmlTextAreaElement = e.target_unchecked_into();
+            text.set(area.value());
+        })
+    };
+
+    let on_run = {
+        let text = text.clone();
+        let paste_callback = props.paste_callback.clone();
+        Callback::from(move |_| paste_callback.emit(trim_input(&text)))
+    };
+
+    let paste_id = format!("paste-day-{}", props.day_num);
+    html! {
+        <details class="paste">
+            <summary class="button">{"📋 Paste..."}</summary>
+            <textarea id={paste_id} rows="8" placeholder="Paste an input here" value={(*text).clone()} oninput={on_input} />
+            <button type="button" onclick={on_run} disabled={text.trim().is_empty()}>{ "▶ Run pasted" }</button>
+        </details>
+    }
+}
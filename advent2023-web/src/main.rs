@@ -1,30 +1,86 @@
 use advent2023_lib::get_days;
 use yew::prelude::*;
 
+use crate::dashboard::Dashboard;
+use crate::router::{use_route, Link, Route};
 use crate::web::{DayBox, DayProps, DayView};
 
+mod dashboard;
+mod examples;
 mod file;
+mod params;
+mod reports;
+mod router;
+mod source;
+mod storage;
 mod web;
 
 #[function_component]
-fn App() -> Html {
-    let mut days = get_days();
-    let mut day_nums: Vec<usize> = days.keys().copied().collect();
-    day_nums.sort_unstable();
+fn Overview() -> Html {
+    let days = get_days();
     html! {
-        <div>
-            <h1>{"Advent of Code"}</h1>
+        <ul class="overview">
+            <li>
+                <Link route={Route::Dashboard}>{"Run every day"}</Link>
+            </li>
             {
-                for day_nums.into_iter().map(|day_num| {
-                    let props = yew::props!(DayProps {
-                        day_num: day_num,
-                        day: DayBox(days.remove(&day_num).unwrap()),
-                    });
-                    html!{
-                        <DayView ..props/>
-                    }
+                for days.iter().map(|(&day_num, day)| html! {
+                    <li>
+                        <Link route={Route::Day(day_num)}>
+                            {"Day "}{day_num}{": "}<em>{day.get_title()}</em>
+                        </Link>
+                    </li>
                 })
             }
+        </ul>
+    }
+}
+
+#[function_component]
+fn App() -> Html {
+    let route = use_route();
+    let mut days = get_days();
+    let on_clear = Callback::from(|_| {
+        storage::clear();
+        if let Some(window) = web_sys::window() {
+            if let Err(e) = window.location().reload() {
+                log::error!("could not reload the page: {:?}", e);
+            }
+        }
+    });
+    let (day_num, example) = match route {
+        Route::Day(day_num) => (Some(day_num), None),
+        Route::Example {
+            day_num,
+            example_num,
+        } => (Some(day_num), Some(example_num - 1)),
+        Route::Overview | Route::Dashboard | Route::NotFound => (None, None),
+    };
+    let page = match day_num.map(|day_num| (day_num, days.remove(&day_num))) {
+        None if route == Route::Overview => html! { <Overview /> },
+        None if route == Route::Dashboard => html! { <Dashboard /> },
+        Some((day_num, Some(day))) => {
+            let props = yew::props!(DayProps {
+                day_num: day_num,
+                day: DayBox(day),
+                example: example,
+            });
+            html! {
+                <DayView key={day_num} ..props/>
+            }
+        },
+        _ => html! {
+            <p class="error">
+                {"There is no such page. "}
+                <Link route={Route::Overview}>{"See every day"}</Link>
+            </p>
+        },
+    };
+    html! {
+        <div>
+            <Link route={Route::Overview}><h1>{"Advent of Code"}</h1></Link>
+            <button type="button" onclick={on_clear}>{ "🗑 Clear my data" }</button>
+            {page}
         </div>
     }
 }
@@ -0,0 +1,45 @@
+use advent2023_lib::{ParamKind, Params};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct ParamProps {
+    pub day_num: usize,
+    pub params: Params,
+    pub params_change_callback: Callback<Params>,
+}
+
+#[function_component]
+pub fn ParamFields(props: &ParamProps) -> Html {
+    html! {
+        <div class="row">
+        {
+            for props.params.specs().iter().map(|spec| {
+                let on_change = {
+                    let params = props.params.clone();
+                    let params_change_callback = props.params_change_callback.clone();
+                    Callback::from(move |e: Event| {
+                        let input: HtmlInputElement = e.target_unchecked_into();
+                        let mut params = params.clone();
+                        match params.set(spec.name, &input.value()) {
+                            Ok(()) => params_change_callback.emit(params),
+                            Err(e) => log::error!("invalid value for {}: {:?}", spec.name, e),
+                        }
+                    })
+                };
+                let input_type = match spec.kind {
+                    ParamKind::Integer => "number",
+                    ParamKind::Text => "text",
+                };
+                let param_id = format!("param-day-{}-{}", props.day_num, spec.name);
+                html! {
+                    <div class="row-item day-param" title={spec.description}>
+                        <label for={param_id.clone()}>{spec.name}{": "}</label>
+                        <input id={param_id} type={input_type} min="0" value={props.params.get(spec.name).to_owned()} onchange={on_change} />
+                    </div>
+                }
+            })
+        }
+        </div>
+    }
+}
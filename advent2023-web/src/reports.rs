@@ -0,0 +1,115 @@
+use advent2023_lib::day04;
+use advent2023_lib::day07::{self, Card, Rules};
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct ReportProps {
+    pub input: String,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct HandReportProps {
+    pub input: String,
+    pub rules: Rules,
+    /// Which rules the hands are ranked with, such as "with jokers".
+    pub label: AttrValue,
+}
+
+fn card_string(cards: &[Card; 5]) -> String {
+    cards.iter().map(|card| card.to_string()).collect()
+}
+
+#[function_component]
+pub fn HandReportTable(props: &HandReportProps) -> Html {
+    // Ranking every hand is slow, so only do it again for another input.
+    let reports = use_memo(
+        (props.input.clone(), props.rules.clone()),
+        |(input, rules)| {
+            day07::parse(input)
+                .ok()
+                .map(|cards| day07::explain(&cards, rules))
+        },
+    );
+    let reports = match &*reports {
+        None => return html! {},
+        Some(Ok(reports)) => reports,
+        Some(Err(e)) => return html! { <p>{e.to_string()}</p> },
+    };
+    html! {
+        <details class="report">
+            <summary class="button">{"Explain ranks "}{&props.label}</summary>
+            <table>
+                <thead>
+                    <tr>
+                        <th>{"Hand"}</th>
+                        <th>{"Type"}</th>
+                        <th>{"Wild"}</th>
+                        <th>{"Effective"}</th>
+                        <th>{"Effective type"}</th>
+                        <th>{"Bid"}</th>
+                        <th>{"Rank"}</th>
+                        <th>{"Winnings"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                {
+                    for reports.iter().map(|report| html! {
+                        <tr>
+                            <td>{card_string(&report.cards)}</td>
+                            <td>{report.hand_type.to_string()}</td>
+                            <td>{report.substitute.map_or(String::from("-"), |card| card.to_string())}</td>
+                            <td>{card_string(&report.effective_cards)}</td>
+                            <td>{report.effective_type.to_string()}</td>
+                            <td>{report.bid}</td>
+                            <td>{report.rank}</td>
+                            <td>{report.winnings}</td>
+                        </tr>
+                    })
+                }
+                </tbody>
+            </table>
+        </details>
+    }
+}
+
+#[function_component]
+pub fn CardCopiesTable(props: &ReportProps) -> Html {
+    // Playing the cascade is slow, so only do it again for another input.
+    let copies = use_memo(props.input.clone(), |input| {
+        day04::parse(input).ok().map(|pile| day04::copies(&pile))
+    });
+    let Some(copies) = &*copies else {
+        return html! {};
+    };
+    let mut total = 0;
+    html! {
+        <details class="report">
+            <summary class="button">{"Show how the pile grows"}</summary>
+            <table>
+                <thead>
+                    <tr>
+                        <th>{"Card"}</th>
+                        <th>{"Matching"}</th>
+                        <th>{"Copies"}</th>
+                        <th>{"Pile"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                {
+                    for copies.iter().map(|card| {
+                        total += card.copies;
+                        html! {
+                            <tr>
+                                <td>{card.id}</td>
+                                <td>{card.matching}</td>
+                                <td>{card.copies}</td>
+                                <td>{total}</td>
+                            </tr>
+                        }
+                    })
+                }
+                </tbody>
+            </table>
+        </details>
+    }
+}
@@ -0,0 +1,124 @@
+use gloo_history::{BrowserHistory, History};
+use yew::prelude::*;
+
+/// A page of the app, with a path of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// Every day, with its title.
+    Overview,
+    /// Every day, solved at once.
+    Dashboard,
+    Day(usize),
+    /// An example of a day, numbered from 1 as it is on the page.
+    Example {
+        day_num: usize,
+        example_num: usize,
+    },
+    NotFound,
+}
+
+/// The path the app is served from, ending in a slash. Trunk sets it in the
+/// `<base>` of the page from `public_url`.
+fn base_path() -> String {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.base_uri().ok().flatten())
+        .and_then(|uri| web_sys::Url::new(&uri).ok())
+        .map_or_else(|| "/".to_owned(), |url| url.pathname())
+}
+
+impl Route {
+    /// Parse a path below the base path.
+    fn parse(path: &str) -> Self {
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let number = |segment: &str| segment.parse::<usize>().ok();
+        match segments[..] {
+            [] => Self::Overview,
+            ["dashboard"] => Self::Dashboard,
+            ["day", day_num] => number(day_num).map_or(Self::NotFound, Self::Day),
+            ["day", day_num, "example", example_num] => {
+                match (number(day_num), number(example_num)) {
+                    (Some(day_num), Some(example_num)) if example_num > 0 => Self::Example {
+                        day_num,
+                        example_num,
+                    },
+                    _ => Self::NotFound,
+                }
+            },
+            _ => Self::NotFound,
+        }
+    }
+
+    /// The path of the page below the base path.
+    fn path(self) -> String {
+        match self {
+            Self::Overview | Self::NotFound => String::new(),
+            Self::Dashboard => "dashboard".to_owned(),
+            Self::Day(day_num) => format!("day/{}", day_num),
+            Self::Example {
+                day_num,
+                example_num,
+            } => format!("day/{}/example/{}", day_num, example_num),
+        }
+    }
+
+    pub fn href(self) -> String {
+        format!("{}{}", base_path(), self.path())
+    }
+
+    /// The page the browser is on.
+    fn current() -> Self {
+        let path = BrowserHistory::new().location().path().to_owned();
+        let base = base_path();
+        match path.strip_prefix(base.trim_end_matches('/')) {
+            Some(path) if path.is_empty() || path.starts_with('/') => Self::parse(path),
+            _ => Self::NotFound,
+        }
+    }
+}
+
+/// The page the browser is on, updated as the user navigates.
+#[hook]
+pub fn use_route() -> Route {
+    let route = use_state(Route::current);
+    {
+        let route = route.clone();
+        use_effect_with((), move |_| {
+            let listener = BrowserHistory::new().listen(move || route.set(Route::current()));
+            move || drop(listener)
+        });
+    }
+    *route
+}
+
+#[derive(Properties, PartialEq)]
+pub struct LinkProps {
+    pub route: Route,
+    #[prop_or_default]
+    pub class: Classes,
+    #[prop_or_default]
+    pub children: Html,
+}
+
+/// A link to a page of the app that is followed without reloading it.
+#[function_component]
+pub fn Link(props: &LinkProps) -> Html {
+    let href = props.route.href();
+    let onclick = {
+        let href = href.clone();
+        Callback::from(move |e: MouseEvent| {
+            // Let the browser open the page elsewhere if it is asked to.
+            if e.button() != 0 || e.ctrl_key() || e.meta_key() || e.shift_key() {
+                return;
+            }
+            e.prevent_default();
+            BrowserHistory::new().push(href.as_str());
+            if let Some(window) = web_sys::window() {
+                window.scroll_to_with_x_and_y(0.0, 0.0);
+            }
+        })
+    };
+    html! {
+        <a class={props.class.clone()} {href} {onclick}>{props.children.clone()}</a>
+    }
+}
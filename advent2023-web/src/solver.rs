@@ -0,0 +1,379 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use advent2023_lib::{
+    get_days, Context, DayTrait, Params, ParseError, Part, Progress, Session, SolveError,
+};
+use js_sys::{Array, Uint8Array};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Blob, BlobPropertyBag, DedicatedWorkerGlobalScope, MessageEvent, Url, Worker};
+use web_time::Instant;
+
+/// Where the page finds the worker built from `src/bin/worker.rs`, relative to
+/// the `<base>` of the page so that it is found from any route.
+pub const WORKER_PATH: &str = "./worker.js";
+
+/// How long each part may run before it is given up on.
+const PART_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long to wait between sending two progress reports of a part.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Solve one or both parts of a day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolveRequest {
+    pub day_num: usize,
+    /// The number of the part to solve, or `None` for both.
+    pub part_num: Option<usize>,
+    pub input: String,
+    /// The parameters that have been set, with their values.
+    pub params: Vec<(String, String)>,
+}
+
+/// The number of a part in requests and responses.
+pub fn part_num(part: Part) -> usize {
+    match part {
+        Part::First => 1,
+        Part::Second => 2,
+    }
+}
+
+/// Why the input or the parameters could not be used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParseFailure {
+    /// The kind of the error, such as the variant of a `ParseError`.
+    pub kind: String,
+    pub message: String,
+    /// The line of the input at fault, numbered from 1, if it is known.
+    pub line: Option<usize>,
+}
+
+impl From<&ParseError> for ParseFailure {
+    fn from(e: &ParseError) -> Self {
+        let message = match e {
+            ParseError::Line { error, .. } => error.to_string(),
+            e => e.to_string(),
+        };
+        Self {
+            kind: e.kind().to_owned(),
+            message,
+            line: e.line(),
+        }
+    }
+}
+
+impl Display for ParseFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// How a part ended.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Outcome {
+    Answer(String),
+    Failed(String),
+    TimedOut,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SolveResponse {
+    /// The input or the parameters are invalid, so neither part is solved.
+    ParseFailed(ParseFailure),
+    Progress {
+        part_num: usize,
+        done: u64,
+        total: Option<u64>,
+    },
+    Solved {
+        part_num: usize,
+        outcome: Outcome,
+        elapsed: Duration,
+    },
+    /// The worker panicked, while solving a part or before it started one, and
+    /// stopped with it. It answers no more requests, so another takes over.
+    Panicked {
+        part_num: Option<usize>,
+        message: String,
+        elapsed: Duration,
+    },
+}
+
+/// A message from the worker to the page.
+#[derive(Debug, Serialize, Deserialize)]
+enum FromWorker {
+    /// The worker listens for requests, so those sent before can be posted.
+    Loaded,
+    Respond(SolveResponse),
+}
+
+fn encode<T: Serialize>(message: &T) -> JsValue {
+    let bytes = bincode::serialize(message).expect("messages are plain data");
+    Uint8Array::from(bytes.as_slice()).into()
+}
+
+fn decode<T: DeserializeOwned>(data: JsValue) -> Option<T> {
+    bincode::deserialize(&Uint8Array::new(&data).to_vec()).ok()
+}
+
+/// Start a worker from the script at `WORKER_PATH`. The script only defines
+/// `wasm_bindgen`, which loads the module and runs the main of the worker.
+fn start_worker() -> Result<Worker, JsValue> {
+    let base = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.base_uri().ok().flatten())
+        .ok_or("the page has no address")?;
+    let script = Url::new_with_base(WORKER_PATH, &base)?.href();
+    let module = format!("{}_bg.wasm", script.trim_end_matches(".js"));
+    let loader =
+        Array::of1(&format!(r#"importScripts("{}");wasm_bindgen("{}");"#, script, module).into());
+    let blob = Blob::new_with_str_sequence_and_options(
+        &loader,
+        BlobPropertyBag::new().type_("application/javascript"),
+    )?;
+    Worker::new(&Url::create_object_url_with_blob(&blob)?)
+}
+
+/// The page's end of a worker that solves days. Dropping it terminates the
+/// worker, stopping the part it is running.
+pub struct SolveBridge {
+    worker: Worker,
+    /// The requests sent before the worker listens, posted once it does.
+    pending: Rc<RefCell<Option<Vec<SolveRequest>>>>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+}
+
+impl SolveBridge {
+    /// Start a worker that answers each request with `callback`.
+    pub fn spawn(callback: impl Fn(SolveResponse) + 'static) -> Result<Self, String> {
+        let worker = start_worker().map_err(|e| format!("the worker did not start: {:?}", e))?;
+        let pending = Rc::new(RefCell::new(Some(Vec::new())));
+        let on_message = {
+            let worker = worker.clone();
+            let pending = Rc::clone(&pending);
+            Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+                match decode(event.data()) {
+                    Some(FromWorker::Loaded) => {
+                        for request in pending.borrow_mut().take().unwrap_or_default() {
+                            post(&worker, &request);
+                        }
+                    },
+                    Some(FromWorker::Respond(response)) => callback(response),
+                    None => log::error!("the worker sent a message that is not a response"),
+                }
+            })
+        };
+        worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        Ok(Self {
+            worker,
+            pending,
+            _on_message: on_message,
+        })
+    }
+
+    pub fn send(&self, request: SolveRequest) {
+        match self.pending.borrow_mut().as_mut() {
+            Some(pending) => pending.push(request),
+            None => post(&self.worker, &request),
+        }
+    }
+}
+
+impl Drop for SolveBridge {
+    fn drop(&mut self) {
+        self.worker.set_onmessage(None);
+        self.worker.terminate();
+    }
+}
+
+fn post(worker: &Worker, request: &SolveRequest) {
+    if let Err(e) = worker.post_message(&encode(request)) {
+        log::error!("could not send a request to the worker: {:?}", e);
+    }
+}
+
+/// Send a message from the worker this runs in to the page.
+fn respond(response: SolveResponse) {
+    let scope: DedicatedWorkerGlobalScope = js_sys::global().unchecked_into();
+    if let Err(e) = scope.post_message(&encode(&FromWorker::Respond(response))) {
+        log::error!("could not send a response to the page: {:?}", e);
+    }
+}
+
+/// Where the request being answered reports the progress of its parts, and
+/// how it ended if it panics.
+struct Reporter {
+    /// The part that is running, if any is.
+    part_num: Option<usize>,
+    started: Instant,
+    last: Option<Instant>,
+}
+
+thread_local! {
+    // The progress sink of a context must be `Send`, so it cannot capture the
+    // state of the request.
+    static REPORTER: RefCell<Option<Reporter>> = const { RefCell::new(None) };
+}
+
+fn report(progress: Progress) {
+    REPORTER.with(|reporter| {
+        let mut reporter = reporter.borrow_mut();
+        let Some(reporter) = reporter.as_mut() else {
+            return;
+        };
+        let Some(part_num) = reporter.part_num else {
+            return;
+        };
+        if reporter
+            .last
+            .is_some_and(|last| last.elapsed() < PROGRESS_INTERVAL)
+        {
+            return;
+        }
+        reporter.last = Some(Instant::now());
+        respond(SolveResponse::Progress {
+            part_num,
+            done: progress.done,
+            total: progress.total,
+        });
+    });
+}
+
+/// Tell the page that the request being answered panicked. It is called by the
+/// panic hook of the worker, as nothing runs after a panic in WebAssembly.
+pub fn report_panic(message: String) {
+    REPORTER.with(|reporter| {
+        // A panic while reporting progress leaves the reporter borrowed.
+        let Ok(reporter) = reporter.try_borrow() else {
+            return;
+        };
+        if let Some(reporter) = reporter.as_ref() {
+            respond(SolveResponse::Panicked {
+                part_num: reporter.part_num,
+                message,
+                elapsed: reporter.started.elapsed(),
+            });
+        }
+    });
+}
+
+/// Solves days off the main thread, keeping the last input it parsed so that
+/// new parameters do not parse it again.
+pub struct SolveWorker {
+    days: BTreeMap<usize, Arc<dyn DayTrait>>,
+    session: Option<(usize, String, Box<dyn Session>)>,
+}
+
+impl SolveWorker {
+    fn params(day: &dyn DayTrait, values: &[(String, String)]) -> Result<Params, ParseFailure> {
+        let mut params = Params::new(day.get_params());
+        for (name, value) in values {
+            params.set(name, value).map_err(|e| ParseFailure {
+                message: format!("parameter {}: {}", name, e),
+                ..ParseFailure::from(&e)
+            })?;
+        }
+        Ok(params)
+    }
+
+    /// Answer the requests the page sends to the worker this runs in.
+    pub fn register() {
+        let scope: DedicatedWorkerGlobalScope = js_sys::global().unchecked_into();
+        let mut worker = Self {
+            days: get_days(),
+            session: None,
+        };
+        let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+            match decode(event.data()) {
+                Some(request) => worker.received(request),
+                None => log::error!("the page sent a message that is not a request"),
+            }
+        });
+        scope.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        // The worker listens for as long as it runs.
+        on_message.forget();
+        if let Err(e) = scope.post_message(&encode(&FromWorker::Loaded)) {
+            log::error!("could not tell the page that the worker is loaded: {:?}", e);
+        }
+    }
+
+    fn received(&mut self, request: SolveRequest) {
+        REPORTER.with(|reporter| {
+            *reporter.borrow_mut() = Some(Reporter {
+                part_num: None,
+                started: Instant::now(),
+                last: None,
+            })
+        });
+        let Some(day) = self.days.get(&request.day_num) else {
+            respond(SolveResponse::ParseFailed(ParseFailure {
+                kind: String::from("Day"),
+                message: format!("there is no day {}", request.day_num),
+                line: None,
+            }));
+            return;
+        };
+        let params = match Self::params(day.as_ref(), &request.params) {
+            Ok(params) => params,
+            Err(e) => {
+                respond(SolveResponse::ParseFailed(e));
+                return;
+            },
+        };
+        let parsed = matches!(
+            &self.session,
+            Some((day_num, input, _session))
+                if *day_num == request.day_num && *input == request.input
+        );
+        if !parsed {
+            match day.session(&request.input) {
+                Ok(session) => self.session = Some((request.day_num, request.input, session)),
+                Err(e) => {
+                    self.session = None;
+                    respond(SolveResponse::ParseFailed(ParseFailure::from(&e)));
+                    return;
+                },
+            }
+        }
+        let (_day_num, _input, session) = self.session.as_ref().expect("the input is parsed");
+        for (part, part_num) in [(Part::First, 1), (Part::Second, 2)] {
+            if request.part_num.is_some_and(|wanted| wanted != part_num) {
+                continue;
+            }
+            let start = Instant::now();
+            REPORTER.with(|reporter| {
+                if let Some(reporter) = reporter.borrow_mut().as_mut() {
+                    reporter.part_num = Some(part_num);
+                    reporter.started = start;
+                    reporter.last = None;
+                }
+            });
+            let context = Context::with_timeout(PART_TIMEOUT).with_progress(report);
+            let outcome = match session.run(part, &params, &context) {
+                Ok(answer) => Outcome::Answer(answer),
+                Err(SolveError::Cancelled) => Outcome::TimedOut,
+                Err(e) => Outcome::Failed(e.to_string()),
+            };
+            REPORTER.with(|reporter| {
+                if let Some(reporter) = reporter.borrow_mut().as_mut() {
+                    reporter.part_num = None;
+                }
+            });
+            respond(SolveResponse::Solved {
+                part_num,
+                outcome,
+                elapsed: start.elapsed(),
+            });
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use yew::prelude::*;
+
+/// The source of each day, embedded when the app is built so that it matches
+/// the solutions that run.
+const SOURCES: [(usize, &str); 8] = [
+    (1, include_str!("../../advent2023-lib/src/day01.rs")),
+    (2, include_str!("../../advent2023-lib/src/day02.rs")),
+    (3, include_str!("../../advent2023-lib/src/day03.rs")),
+    (4, include_str!("../../advent2023-lib/src/day04.rs")),
+    (5, include_str!("../../advent2023-lib/src/day05.rs")),
+    (6, include_str!("../../advent2023-lib/src/day06.rs")),
+    (7, include_str!("../../advent2023-lib/src/day07.rs")),
+    (8, include_str!("../../advent2023-lib/src/day08.rs")),
+];
+
+fn day_source(day_num: usize) -> Option<&'static str> {
+    SOURCES
+        .iter()
+        .find(|&&(num, _source)| num == day_num)
+        .map(|&(_num, source)| source)
+}
+
+const KEYWORDS: [&str; 38] = [
+    "as", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn",
+    "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe",
+    "use", "where", "while", "async", "await",
+];
+
+/// The number of `#` of a raw string that the source starts with, if it does.
+fn raw_hashes(source: &str) -> Option<usize> {
+    let hashes = source
+        .strip_prefix('r')?
+        .bytes()
+        .take_while(|&b| b == b'#')
+        .count();
+    (source.as_bytes().get(1 + hashes) == Some(&b'"')).then_some(hashes)
+}
+
+/// Split Rust source into pieces, each with the class it is shown with, if
+/// any. It only needs to be good enough to read the days by.
+fn tokens(source: &str) -> Vec<(Option<&'static str>, &str)> {
+    let bytes = source.as_bytes();
+    let ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let mut tokens = Vec::new();
+    let mut plain = 0;
+    let mut start = 0;
+    while start < bytes.len() {
+        let rest = &source[start..];
+        let (class, len) = if rest.starts_with("//") {
+            (Some("comment"), rest.find('\n').unwrap_or(rest.len()))
+        } else if rest.starts_with("/*") {
+            (
+                Some("comment"),
+                rest.find("*/").map_or(rest.len(), |end| end + 2),
+            )
+        } else if bytes[start] == b'"' || raw_hashes(rest).is_some() {
+            let (open, close, escapes) = match raw_hashes(rest) {
+                Some(hashes) => (2 + hashes, format!("\"{}", "#".repeat(hashes)), false),
+                None => (1, String::from("\""), true),
+            };
+            let mut end = open;
+            while end < rest.len() && !rest.as_bytes()[end..].starts_with(close.as_bytes()) {
+                end += if escapes && rest.as_bytes()[end] == b'\\' {
+                    2
+                } else {
+                    1
+                };
+            }
+            (Some("string"), (end + close.len()).min(rest.len()))
+        } else if bytes[start] == b'\'' {
+            let rest_bytes = rest.as_bytes();
+            if rest_bytes.get(1) == Some(&b'\\') {
+                (
+                    Some("string"),
+                    rest[3..].find('\'').map_or(rest.len(), |end| end + 4),
+                )
+            } else if rest_bytes.get(2) == Some(&b'\'') {
+                (Some("string"), 3)
+            } else {
+                let len = 1 + rest_bytes[1..].iter().take_while(|&&b| ident(b)).count();
+                (Some("lifetime"), len)
+            }
+        } else if bytes[start].is_ascii_digit() {
+            (
+                Some("number"),
+                rest.bytes().take_while(|&b| ident(b)).count(),
+            )
+        } else if ident(bytes[start]) {
+            let len = rest.bytes().take_while(|&b| ident(b)).count();
+            let word = &rest[..len];
+            let class = if KEYWORDS.contains(&word) {
+                Some("keyword")
+            } else if rest[len..].starts_with('!') {
+                Some("macro")
+            } else if word.starts_with(|c: char| c.is_ascii_uppercase()) {
+                Some("type")
+            } else {
+                None
+            };
+            (class, len)
+        } else {
+            (None, rest.chars().next().map_or(1, char::len_utf8))
+        };
+        if class.is_some() {
+            if plain < start {
+                tokens.push((None, &source[plain..start]));
+            }
+            tokens.push((class, &source[start..start + len]));
+            plain = start + len;
+        }
+        start += len;
+    }
+    if plain < source.len() {
+        tokens.push((None, &source[plain..]));
+    }
+    tokens
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SourceProps {
+    pub day_num: usize,
+}
+
+/// The source of a day, highlighted, shown when it is opened.
+#[function_component]
+pub fn SourceView(props: &SourceProps) -> Html {
+    let open = use_state(|| false);
+    let on_toggle = {
+        let open = open.clone();
+        Callback::from(move |e: Event| {
+            let details: web_sys::HtmlDetailsElement = e.target_unchecked_into();
+            open.set(details.open());
+        })
+    };
+    let Some(source) = day_source(props.day_num) else {
+        return html! {};
+    };
+    html! {
+        <details class="source" ontoggle={on_toggle}>
+            <summary class="button">{"Source Code"}</summary>
+            {
+                // Only highlight the source once it is shown.
+                if *open {
+                    html! {
+                        <pre>
+                            {
+                                for tokens(source).into_iter().map(|(class, text)| match class {
+                                    Some(class) => html! { <span {class}>{text}</span> },
+                                    None => html! { {text} },
+                                })
+                            }
+                        </pre>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+        </details>
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use advent2023_web::solver::Outcome;
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+const RUN_PREFIX: &str = "advent2023-day-";
+const INPUT_PREFIX: &str = "advent2023-input-";
+
+/// The latest run of a day, saved so that it survives a reload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedRun {
+    pub input: String,
+    /// The number of the part that was solved, or `None` for both.
+    pub part_num: Option<usize>,
+    pub expected: [Option<String>; 2],
+    pub source: String,
+    /// Whether the input is an example rather than a puzzle input.
+    #[serde(default)]
+    pub example: bool,
+    /// The parameters that had been set, with their values.
+    pub params: Vec<(String, String)>,
+    /// How each part ended, if it did.
+    pub outcomes: [Option<(Outcome, Duration)>; 2],
+}
+
+/// The latest puzzle input of a day, kept apart from the latest run so that
+/// running an example after it does not lose it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedInput {
+    pub input: String,
+    pub source: String,
+    /// The parameters that had been set, with their values.
+    pub params: Vec<(String, String)>,
+}
+
+fn key(prefix: &str, day_num: usize) -> String {
+    format!("{}{:02}", prefix, day_num)
+}
+
+pub fn load(day_num: usize) -> Option<SavedRun> {
+    LocalStorage::get(key(RUN_PREFIX, day_num)).ok()
+}
+
+pub fn load_input(day_num: usize) -> Option<SavedInput> {
+    LocalStorage::get(key(INPUT_PREFIX, day_num)).ok()
+}
+
+/// Save the latest run of a day, and its input too unless it is an example.
+pub fn save(day_num: usize, run: &SavedRun) {
+    if let Err(e) = LocalStorage::set(key(RUN_PREFIX, day_num), run) {
+        log::error!("could not save day {}: {}", day_num, e);
+    }
+    if !run.example {
+        let input = SavedInput {
+            input: run.input.clone(),
+            source: run.source.clone(),
+            params: run.params.clone(),
+        };
+        if let Err(e) = LocalStorage::set(key(INPUT_PREFIX, day_num), input) {
+            log::error!("could not save the input of day {}: {}", day_num, e);
+        }
+    }
+}
+
+/// Forget the saved runs and inputs of every day.
+pub fn clear() {
+    let storage = LocalStorage::raw();
+    let keys: Vec<String> = (0..LocalStorage::length())
+        .filter_map(|index| storage.key(index).ok().flatten())
+        .filter(|key| key.starts_with(RUN_PREFIX) || key.starts_with(INPUT_PREFIX))
+        .collect();
+    for key in keys {
+        LocalStorage::delete(key);
+    }
+}
@@ -1,11 +1,272 @@
 // use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
 
-use advent2023_lib::{DayTrait, ParseResult};
+use advent2023_lib::day07::Rules;
+use advent2023_lib::{DayTrait, Example, Params, Part, Progress};
+use advent2023_web::solver::{
+    part_num, Outcome, ParseFailure, SolveBridge, SolveRequest, SolveResponse,
+};
+use gloo_timers::callback::Interval;
+use web_time::Instant;
 use yew::prelude::*;
 
-use crate::file::FileUpload;
+use crate::examples::{example_label, ExampleList};
+use crate::file::{DropZone, FileUpload, LoadResult, PasteInput};
+use crate::params::ParamFields;
+use crate::reports::{CardCopiesTable, HandReportTable};
+use crate::router::{Link, Route};
+use crate::source::SourceView;
+use crate::storage::{self, SavedRun};
 
-pub struct DayBox(pub Box<dyn DayTrait>);
+/// Where a part of the latest run has got to.
+#[derive(Debug, Clone, PartialEq, Default)]
+enum PartState {
+    #[default]
+    Idle,
+    Running {
+        progress: Option<Progress>,
+    },
+    Finished {
+        outcome: Outcome,
+        elapsed: Duration,
+        progress: Option<Progress>,
+    },
+    Cancelled {
+        progress: Option<Progress>,
+    },
+}
+
+/// What a run solves, kept so that it can be solved again with new
+/// parameters.
+#[derive(Debug, Clone, PartialEq)]
+struct Target {
+    input: String,
+    /// The part to solve, or `None` for both.
+    part: Option<Part>,
+    /// The answers the puzzle gives, if the input is an example.
+    expected: [Option<String>; 2],
+    /// Where the input came from.
+    source: String,
+    /// Whether the input is an example rather than a puzzle input.
+    example: bool,
+}
+
+/// The latest run of a day in the worker.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Run {
+    target: Option<Target>,
+    started: Option<Instant>,
+    parse_error: Option<ParseFailure>,
+    parts: [PartState; 2],
+    /// Whether the run was saved by an earlier visit to the page.
+    restored: bool,
+    /// Whether the worker panicked, so that the next run needs a new one.
+    panicked: bool,
+}
+
+enum RunAction {
+    Start(Target),
+    Respond(SolveResponse),
+    Cancel,
+}
+
+impl Run {
+    fn running(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|part| matches!(part, PartState::Running { .. }))
+    }
+
+    fn restore(saved: SavedRun) -> Self {
+        Self {
+            target: Some(Target {
+                input: saved.input,
+                part: saved.part_num.map(|part_num| match part_num {
+                    1 => Part::First,
+                    _ => Part::Second,
+                }),
+                expected: saved.expected,
+                source: saved.source,
+                example: saved.example,
+            }),
+            started: None,
+            parse_error: None,
+            parts: saved.outcomes.map(|outcome| match outcome {
+                Some((outcome, elapsed)) => PartState::Finished {
+                    outcome,
+                    elapsed,
+                    progress: None,
+                },
+                None => PartState::Idle,
+            }),
+            restored: true,
+            panicked: false,
+        }
+    }
+
+    /// The run to save, once it has finished.
+    fn to_saved(&self, params: &Params) -> Option<SavedRun> {
+        if self.running() || self.parse_error.is_some() {
+            return None;
+        }
+        let target = self.target.as_ref()?;
+        Some(SavedRun {
+            input: target.input.clone(),
+            part_num: target.part.map(part_num),
+            expected: target.expected.clone(),
+            source: target.source.clone(),
+            example: target.example,
+            params: params
+                .values()
+                .map(|(name, value)| (name.to_owned(), value.to_owned()))
+                .collect(),
+            outcomes: self.parts.clone().map(|part| match part {
+                PartState::Finished {
+                    outcome, elapsed, ..
+                } => Some((outcome, elapsed)),
+                _ => None,
+            }),
+        })
+    }
+}
+
+impl Reducible for Run {
+    type Action = RunAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut run = (*self).clone();
+        match action {
+            RunAction::Start(target) => {
+                let running = |part| match target.part {
+                    Some(wanted) if wanted != part => PartState::Idle,
+                    _ => PartState::Running { progress: None },
+                };
+                run = Self {
+                    parts: [running(Part::First), running(Part::Second)],
+                    target: Some(target),
+                    started: Some(Instant::now()),
+                    parse_error: None,
+                    restored: false,
+                    panicked: false,
+                };
+            },
+            RunAction::Respond(SolveResponse::ParseFailed(e)) => {
+                log::error!("parsing error: {}", e);
+                run.parse_error = Some(e);
+                run.parts = Default::default();
+            },
+            RunAction::Respond(SolveResponse::Progress {
+                part_num,
+                done,
+                total,
+            }) => {
+                if let PartState::Running { progress } = &mut run.parts[part_num - 1] {
+                    *progress = Some(Progress { done, total });
+                }
+            },
+            RunAction::Respond(SolveResponse::Solved {
+                part_num,
+                outcome,
+                elapsed,
+            }) => {
+                let progress = match &run.parts[part_num - 1] {
+                    PartState::Running { progress } => *progress,
+                    _ => None,
+                };
+                run.parts[part_num - 1] = PartState::Finished {
+                    outcome,
+                    elapsed,
+                    progress,
+                };
+            },
+            RunAction::Respond(SolveResponse::Panicked {
+                part_num: None,
+                message,
+                ..
+            }) => {
+                log::error!("the worker panicked: {}", message);
+                run.parse_error = Some(ParseFailure {
+                    kind: String::from("Panic"),
+                    message: format!("the worker panicked, {}", message),
+                    line: None,
+                });
+                run.parts = Default::default();
+                run.panicked = true;
+            },
+            RunAction::Respond(SolveResponse::Panicked {
+                part_num: Some(part_num),
+                message,
+                elapsed,
+            }) => {
+                for (index, part) in run.parts.iter_mut().enumerate() {
+                    let PartState::Running { progress } = part else {
+                        continue;
+                    };
+                    let (outcome, elapsed) = if index + 1 == part_num {
+                        (format!("panicked, {}", message), elapsed)
+                    } else {
+                        (
+                            format!("not run, part {} panicked", part_num),
+                            Duration::ZERO,
+                        )
+                    };
+                    *part = PartState::Finished {
+                        outcome: Outcome::Failed(outcome),
+                        elapsed,
+                        progress: *progress,
+                    };
+                }
+                run.panicked = true;
+            },
+            RunAction::Cancel => {
+                for part in &mut run.parts {
+                    if let PartState::Running { progress } = part {
+                        *part = PartState::Cancelled {
+                            progress: *progress,
+                        };
+                    }
+                }
+            },
+        }
+        Rc::new(run)
+    }
+}
+
+fn progress_bar(progress: Option<Progress>) -> Html {
+    match progress {
+        Some(Progress {
+            done,
+            total: Some(total),
+        }) => html! {
+            <progress value={done.to_string()} max={total.to_string()} />
+        },
+        _ => html! { <progress /> },
+    }
+}
+
+/// The lines of an input around one of them, numbered from 1, which is marked.
+fn input_excerpt(input: &str, line: usize) -> Html {
+    const CONTEXT: usize = 3;
+    let first = line.saturating_sub(CONTEXT).max(1);
+    html! {
+        <pre class="excerpt">
+            {
+                for input.lines().enumerate().skip(first - 1).take(line + CONTEXT + 1 - first).map(|(index, text)| {
+                    let number = html! { <span class="line-num">{format!("{:>4} ", index + 1)}</span> };
+                    if index + 1 == line {
+                        html! { <><mark>{number}{text}</mark>{"\n"}</> }
+                    } else {
+                        html! { <>{number}{text}{"\n"}</> }
+                    }
+                })
+            }
+        </pre>
+    }
+}
+
+pub struct DayBox(pub Arc<dyn DayTrait>);
 
 impl PartialEq for DayBox {
     fn eq(&self, other: &Self) -> bool {
@@ -17,110 +278,299 @@ impl PartialEq for DayBox {
 pub struct DayProps {
     pub day_num: usize,
     pub day: DayBox,
+    /// The index of the example to show, if one was linked to.
+    #[prop_or_default]
+    pub example: Option<usize>,
 }
 
 #[function_component]
 pub fn DayView(props: &DayProps) -> Html {
-    let text_format = props.day.0.get_display();
-    let part_calculate_func = props.day.0.get_both_func();
-    let get_messages = move |input: &str| -> Vec<String> {
-        let result: ParseResult<(String, String)> = part_calculate_func(input);
-        match result {
-            Err(_e) => {
-                log::error!("parsing error...");
-                vec!["Parsing error, please try again...".to_owned()]
-            },
-            Ok(answer) => {
-                let part1 = format!("Part 1: {}", text_format.0.replace("{answer}", &answer.0));
-                let part2 = format!("Part 2: {}", text_format.1.replace("{answer}", &answer.1));
-                log::info!("{}", part1);
-                log::info!("{}", part2);
-                vec![part1, part2]
-            },
-        }
+    let saved = use_memo(props.day_num, |&day_num| storage::load(day_num));
+    let run = {
+        let saved = saved.clone();
+        use_reducer(move || (*saved).clone().map(Run::restore).unwrap_or_default())
+    };
+    let params = {
+        let specs = props.day.0.get_params();
+        use_state(|| {
+            let mut params = Params::new(specs);
+            for (name, value) in saved.iter().flat_map(|saved| &saved.params) {
+                if let Err(e) = params.set(name, value) {
+                    log::warn!("ignoring saved parameter {}: {:?}", name, e);
+                }
+            }
+            params
+        })
     };
+    {
+        let day_num = props.day_num;
+        use_effect_with(run.to_saved(&params), move |saved| {
+            if let Some(saved) = saved {
+                storage::save(day_num, saved);
+            }
+        });
+    }
+    let bridge = use_mut_ref(|| None::<SolveBridge>);
 
-    let messages = use_state(|| Vec::new());
+    // Send a run to the worker. A busy worker cannot take another run, so it
+    // is terminated and a new worker takes over.
+    let solve = {
+        let day_num = props.day_num;
+        let run = run.clone();
+        let bridge = bridge.clone();
+        move |target: Target, params: &Params| {
+            let mut bridge = bridge.borrow_mut();
+            if run.running() || run.panicked || bridge.is_none() {
+                *bridge = None;
+                let dispatcher = run.dispatcher();
+                match SolveBridge::spawn(move |response| {
+                    dispatcher.dispatch(RunAction::Respond(response))
+                }) {
+                    Ok(worker) => *bridge = Some(worker),
+                    Err(e) => log::error!("{}", e),
+                }
+            }
+            let request = SolveRequest {
+                day_num,
+                part_num: target.part.map(part_num),
+                input: target.input.clone(),
+                params: params
+                    .values()
+                    .map(|(name, value)| (name.to_owned(), value.to_owned()))
+                    .collect(),
+            };
+            run.dispatch(RunAction::Start(target));
+            match bridge.as_ref() {
+                Some(worker) => worker.send(request),
+                None => run.dispatch(RunAction::Respond(SolveResponse::ParseFailed(
+                    ParseFailure {
+                        kind: String::from("Worker"),
+                        message: String::from("the worker did not start"),
+                        line: None,
+                    },
+                ))),
+            }
+        }
+    };
 
     let on_run_example = {
-        let example = props.day.0.get_example();
-        let messages = messages.clone();
-        let get_messages = get_messages.clone();
-        Callback::from(move |_| {
-            log::info!("Running Example");
-            messages.set(get_messages(example));
-        })
+        let params = params.clone();
+        let solve = solve.clone();
+        Callback::from(
+            move |(index, part, example): (usize, Option<Part>, Example)| {
+                log::info!("Running Example");
+                solve(
+                    Target {
+                        input: example.input.to_owned(),
+                        part,
+                        expected: example.answers.map(|answer| answer.map(str::to_owned)),
+                        source: example_label(index, part),
+                        example: true,
+                    },
+                    &params,
+                );
+            },
+        )
     };
 
+    let load_error = use_state(|| None::<String>);
+
     let on_file_load = {
-        let messages_state = messages.clone();
-        let get_messages = get_messages.clone();
+        let params = params.clone();
+        let solve = solve.clone();
+        let load_error = load_error.clone();
+        Callback::from(move |loaded: LoadResult| match loaded {
+            Ok(file) => {
+                log::info!("Running Loaded File");
+                load_error.set(None);
+                solve(
+                    Target {
+                        input: file.text,
+                        part: None,
+                        expected: [None, None],
+                        source: format!("From {}", file.name),
+                        example: false,
+                    },
+                    &params,
+                );
+            },
+            Err(e) => {
+                log::error!("loading error: {}", e);
+                load_error.set(Some(format!("Could not load the input: {}", e)));
+            },
+        })
+    };
+
+    let on_paste = {
+        let params = params.clone();
+        let solve = solve.clone();
+        let load_error = load_error.clone();
         Callback::from(move |input: String| {
-            log::info!("Running Loaded File");
-            let mut messages = get_messages(&input);
-            messages.insert(0, String::from("From Upload"));
-            messages_state.set(messages);
+            log::info!("Running Pasted Input");
+            load_error.set(None);
+            solve(
+                Target {
+                    input,
+                    part: None,
+                    expected: [None, None],
+                    source: String::from("Pasted"),
+                    example: false,
+                },
+                &params,
+            );
         })
     };
 
-    let show_input = use_state(|| false);
+    let on_rerun = {
+        let target = run.target.clone();
+        let params = params.clone();
+        let solve = solve.clone();
+        Callback::from(move |_| {
+            if let Some(target) = &target {
+                log::info!("Running Saved Input");
+                solve(target.clone(), &params);
+            }
+        })
+    };
+
+    let on_params_change = {
+        let target = run.target.clone();
+        let params_state = params.clone();
+        Callback::from(move |params: Params| {
+            if let Some(target) = &target {
+                log::info!("Running with new parameters");
+                solve(target.clone(), &params);
+            }
+            params_state.set(params);
+        })
+    };
 
-    let on_collapse = {
-        let show_input = show_input.clone();
+    let on_cancel = {
+        let run = run.clone();
+        let bridge = bridge.clone();
         Callback::from(move |_| {
-            show_input.set(!*show_input);
+            log::info!("Cancelling");
+            // Dropping the worker terminates it, stopping the part it runs.
+            bridge.borrow_mut().take();
+            run.dispatch(RunAction::Cancel);
         })
     };
 
-    let example = props.day.0.get_example();
+    // Redraw the elapsed time while the worker is busy.
+    let redraw = use_force_update();
+    use_effect_with(run.running(), move |&running| {
+        let interval = running.then(|| Interval::new(100, move || redraw.force_update()));
+        move || drop(interval)
+    });
+
+    let text_format = props.day.0.get_display();
     html! {
-        <section class={if props.day_num & 1 != 0 { "day-odd" } else { "day-even" }}>
+        <section>
+        <DropZone class={if props.day_num & 1 != 0 { "day-odd" } else { "day-even" }} file_load_callback={on_file_load.clone()}>
             <div class="row">
-                <a class="row-item day-key" href={format!("https://adventofcode.com/2022/day/{}", props.day_num)}><h4>{"Day "}{props.day_num}{":"}</h4></a>
-                <a class="row-item day-title" href={format!("https://adventofcode.com/2022/day/{}", props.day_num)}><h2><em>{props.day.0.get_title()}</em></h2></a>
-                <a class="row-item day-url" href={format!("https://github.com/droogmic/advent2022/blob/main/advent2022-lib/src/day{:02}.rs", props.day_num)}>{"Source Code"}</a>
+                <Link class="row-item day-key" route={Route::Day(props.day_num)}><h4>{"Day "}{props.day_num}{":"}</h4></Link>
+                <a class="row-item day-title" href={format!("https://adventofcode.com/2023/day/{}", props.day_num)}><h2><em>{props.day.0.get_title()}</em></h2></a>
             </div>
+            <SourceView day_num={props.day_num} />
             <div class="row row-reverse">
                 <FileUpload day_num={props.day_num} file_load_callback={on_file_load} />
-                <div class="row-item day-run">
-                    <button type="button" onclick={on_run_example}>{ "▶ Run..." }</button>
+                <div class="row-item day-paste">
+                    <PasteInput day_num={props.day_num} paste_callback={on_paste} />
                 </div>
-                <div class="row-item day-collapse">
-                    <h5 class={if example.lines().count() > 1 {"button"} else {"button disabled"}} onclick={on_collapse}>
-                    {
-                        if example.lines().count() > 1 {
-                            if *show_input {
-                                "▼ Example: "
-                            } else {
-                                "▬ Example: "
-                            }
-                        } else {
-                            "▬ Example: "
-                        }
-                    }
-                    </h5>
+                <div class="row-item day-run">
+                    <button type="button" onclick={on_cancel} disabled={!run.running()}>{ "■ Cancel" }</button>
                 </div>
             </div>
+            <ExampleList day_num={props.day_num} examples={props.day.0.get_all_examples()} selected={props.example} run_callback={on_run_example} />
+            <ParamFields day_num={props.day_num} params={(*params).clone()} params_change_callback={on_params_change} />
+                {
+                    for load_error.iter().map(|e| html! { <p class="error">{e}</p> })
+                }
+                {
+                    match run.started {
+                        Some(started) if run.running() => html! {
+                            <p class="running">{format!("Running for {:.1?}", started.elapsed())}</p>
+                        },
+                        _ => html! {},
+                    }
+                }
                 {
-                    if *show_input {
+                    for run.target.iter().map(|target| html! { <p>{&target.source}</p> })
+                }
+                {
+                    if run.restored {
                         html! {
-                            <pre>{example}</pre>
+                            <p>
+                                {"Saved from an earlier visit "}
+                                <button type="button" onclick={on_rerun}>{ "↻ Re-run" }</button>
+                            </p>
                         }
                     } else {
-                        html! {
-                            <pre class={if example.lines().count() > 1 {"collapsed"} else {""}}>
-                                {example.lines().take(2).collect::<Vec<_>>().join("\n")}
-                            </pre>
-                        }
+                        html! {}
                     }
                 }
                 {
-                    for messages.iter().map(|message| {
-                        html! {
-                            <p>{message}</p>
+                    match (&run.parse_error, &run.target) {
+                        (Some(failure), Some(target)) => html! {
+                            <>
+                                <p class="error">{format!("The input is invalid ({}), {}", failure.kind, failure)}</p>
+                                {failure.line.map_or(html! {}, |line| input_excerpt(&target.input, line))}
+                            </>
+                        },
+                        _ => html! {},
+                    }
+                }
+                {
+                    for run.parts.iter().zip([text_format.0, text_format.1]).enumerate().map(|(index, (part, format))| {
+                        let part_num = index + 1;
+                        let expected = run.target.as_ref().and_then(|target| target.expected[index].as_ref());
+                        match part {
+                            PartState::Idle => html! {},
+                            PartState::Running { progress } => html! {
+                                <p>{format!("Part {}: running ", part_num)}{progress_bar(*progress)}</p>
+                            },
+                            PartState::Finished { outcome: Outcome::Answer(answer), elapsed, .. } => html! {
+                                <p>
+                                    {format!("Part {}: {}", part_num, format.replace("{answer}", answer))}
+                                    <span class="elapsed">{format!(" ({:.2?})", elapsed)}</span>
+                                    {
+                                        match expected {
+                                            Some(expected) if expected == answer => html! {
+                                                <span class="expected">{" ✔ as expected"}</span>
+                                            },
+                                            Some(expected) => html! {
+                                                <span class="unexpected">{format!(" ✘ expected {}", expected)}</span>
+                                            },
+                                            None => html! {},
+                                        }
+                                    }
+                                </p>
+                            },
+                            PartState::Finished { outcome: Outcome::Failed(e), .. } => html! {
+                                <p>{format!("Part {}: no answer, {}", part_num, e)}</p>
+                            },
+                            PartState::Finished { outcome: Outcome::TimedOut, progress, .. } => html! {
+                                <p>{format!("Part {}: timed out ", part_num)}{progress.map_or(html! {}, |progress| progress_bar(Some(progress)))}</p>
+                            },
+                            PartState::Cancelled { progress } => html! {
+                                <p>{format!("Part {}: cancelled ", part_num)}{progress.map_or(html! {}, |progress| progress_bar(Some(progress)))}</p>
+                            },
                         }
                     })
                 }
+                {
+                    match (props.day_num, &run.target) {
+                        (4, Some(target)) => html! { <CardCopiesTable input={target.input.clone()} /> },
+                        (7, Some(target)) => html! {
+                            <>
+                                <HandReportTable input={target.input.clone()} rules={Rules::standard()} label="without jokers" />
+                                <HandReportTable input={target.input.clone()} rules={Rules::jokers()} label="with jokers" />
+                            </>
+                        },
+                        _ => html! {},
+                    }
+                }
+        </DropZone>
         </section>
     }
 }